@@ -0,0 +1,405 @@
+// clay!(builder, id("Name"), layout { padding: (16, 8) }, rectangle { color: rgb(140, 140, 140) }, any_element
+//     => { text!("File", font_id, size = 16); clay!(builder, ...); })
+// Inside children a bare text! is always a Text element, the formatting macro is
+// reached by its path, clay::text!(builder, "{} items", count)
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{
+    braced, parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+    visit_mut::{self, VisitMut},
+    Block, Expr, ExprCall, ExprLit, ExprPath, Ident, Lit, Macro, Stmt, Token,
+};
+
+pub(crate) struct ClayInput {
+    builder: Ident,
+    configs: Vec<Config>,
+    children: Option<Block>,
+}
+
+enum Config {
+    // id("Name") or idi("Name", index)
+    Id(Ident, Punctuated<Expr, Token![,]>),
+    // layout { padding: (16, 8) }
+    Struct(Ident, Punctuated<Field, Token![,]>),
+    // Anything implementing clay::Element
+    Element(Expr),
+}
+
+struct Field {
+    name: Ident,
+    value: Expr,
+}
+
+impl Parse for Field {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        // Shorthand like struct literals, rectangle { color }
+        if input.is_empty() || input.peek(Token![,]) {
+            let value = syn::parse_quote!(#name);
+            return Ok(Self { name, value });
+        }
+        input.parse::<Token![:]>()?;
+        let value = input.parse()?;
+        Ok(Self { name, value })
+    }
+}
+
+impl Parse for ClayInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let builder = input.parse()?;
+        let mut configs = Vec::new();
+        let mut children = None;
+        while !input.is_empty() {
+            if input.peek(Token![=>]) {
+                input.parse::<Token![=>]>()?;
+                children = Some(input.parse()?);
+                break;
+            }
+            input.parse::<Token![,]>()?;
+            if input.is_empty() || input.peek(Token![=>]) {
+                continue;
+            }
+            configs.push(input.parse()?);
+        }
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after children"));
+        }
+        Ok(Self {
+            builder,
+            configs,
+            children,
+        })
+    }
+}
+
+impl Parse for Config {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident) && input.peek2(syn::token::Brace) {
+            let kind: Ident = input.parse()?;
+            let content;
+            braced!(content in input);
+            return Ok(Config::Struct(
+                kind,
+                content.parse_terminated(Field::parse, Token![,])?,
+            ));
+        }
+        let fork = input.fork();
+        if let Ok(kind) = fork.parse::<Ident>() {
+            if (kind == "id" || kind == "idi") && fork.peek(syn::token::Paren) {
+                input.parse::<Ident>()?;
+                let content;
+                parenthesized!(content in input);
+                return Ok(Config::Id(
+                    kind,
+                    content.parse_terminated(Expr::parse, Token![,])?,
+                ));
+            }
+        }
+        Ok(Config::Element(input.parse()?))
+    }
+}
+
+pub(crate) fn expand(input: ClayInput) -> TokenStream {
+    let builder = &input.builder;
+    let attaches = input.configs.iter().map(|config| match config {
+        Config::Id(kind, args) => {
+            let args = args.iter().collect::<Vec<_>>();
+            match (kind.to_string().as_str(), args.as_slice()) {
+                // String literal ids are hashed at compile time
+                (
+                    "id",
+                    [name @ Expr::Lit(ExprLit {
+                        lit: Lit::Str(_), ..
+                    })],
+                ) => quote_spanned! {kind.span()=>
+                    ::clay::Element::attach(&::clay::clay_id!(#name), #builder);
+                },
+                ("id", [name]) => quote_spanned! {kind.span()=>
+                    ::clay::Element::attach(&::clay::ElementId::new_id((#name).into()), #builder);
+                },
+                ("idi", [name, index]) => quote_spanned! {kind.span()=>
+                    ::clay::Element::attach(
+                        &::clay::ElementId::new_idi((#name).into(), (#index) as u32),
+                        #builder,
+                    );
+                },
+                ("id", _) => syn::Error::new(kind.span(), "expected id(\"Name\")").to_compile_error(),
+                _ => syn::Error::new(kind.span(), "expected idi(\"Name\", index)").to_compile_error(),
+            }
+        }
+        Config::Struct(kind, fields) => {
+            if let Some(error) = duplicate_field(fields.iter().map(|field| &field.name)) {
+                return error;
+            }
+            let ty = match kind.to_string().as_str() {
+                "layout" => quote!(Layout),
+                "rectangle" => quote!(Rectangle),
+                "border" => quote!(Border),
+                "floating" => quote!(Floating),
+                "scroll" => quote!(Scroll),
+                "image" => quote!(Image),
+                _ => {
+                    return syn::Error::new(
+                        kind.span(),
+                        format!(
+                            "unknown clay config `{kind}`, expected one of layout, rectangle, border, floating, scroll, image"
+                        ),
+                    )
+                    .to_compile_error()
+                }
+            };
+            let fields = fields.iter().map(|Field { name, value }| {
+                let value = field_value(name, value);
+                quote!(#name: #value)
+            });
+            quote_spanned! {kind.span()=>
+                {
+                    #[allow(clippy::needless_update, clippy::redundant_field_names)]
+                    let config = ::clay::#ty { #(#fields,)* ..::clay::default() };
+                    ::clay::Element::attach(&config, #builder);
+                }
+            }
+        }
+        Config::Element(element) => quote_spanned! {element.span()=>
+            ::clay::Element::attach(&(#element), #builder);
+        },
+    });
+    let children = match &input.children {
+        Some(children) => {
+            let mut children = children.clone();
+            TextRewriter { builder }.rewrite(&mut children)
+        }
+        None => quote!(::clay::no_children),
+    };
+    quote! {
+        #builder.build(
+            |#builder| { #(#attaches)* },
+            #children,
+        )
+    }
+}
+
+impl TextRewriter<'_> {
+    fn rewrite(mut self, children: &mut Block) -> TokenStream {
+        self.visit_block_mut(children);
+        let builder = self.builder;
+        quote!(|#builder| #children)
+    }
+}
+
+fn duplicate_field<'a>(names: impl Iterator<Item = &'a Ident>) -> Option<TokenStream> {
+    let mut seen = Vec::new();
+    for name in names {
+        if seen.contains(&name) {
+            return Some(
+                syn::Error::new(
+                    name.span(),
+                    format!("field `{name}` specified more than once"),
+                )
+                .to_compile_error(),
+            );
+        }
+        seen.push(name);
+    }
+    None
+}
+
+// Sugar for common field values, anything else is passed through unchanged
+fn field_value(name: &Ident, value: &Expr) -> TokenStream {
+    if let Some(color) = color(value) {
+        return color;
+    }
+    let name = name.to_string();
+    match (name.as_str(), value) {
+        ("padding", Expr::Tuple(tuple)) if tuple.elems.len() == 2 => {
+            let (x, y) = (&tuple.elems[0], &tuple.elems[1]);
+            quote!(::clay::Padding { x: #x, y: #y })
+        }
+        ("sizing", Expr::Tuple(tuple)) if tuple.elems.len() == 2 => {
+            let width = sizing_axis(&tuple.elems[0]);
+            let height = sizing_axis(&tuple.elems[1]);
+            quote!(::clay::Sizing { width: #width, height: #height })
+        }
+        (
+            "corner_radius",
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(_) | Lit::Float(_),
+                ..
+            }),
+        ) => {
+            quote!(::clay::CornerRadius::new((#value) as f32))
+        }
+        ("left" | "right" | "top" | "bottom" | "between_children", Expr::Tuple(tuple))
+            if tuple.elems.len() == 2 =>
+        {
+            let width = &tuple.elems[0];
+            let color = field_value(&Ident::new("color", tuple.span()), &tuple.elems[1]);
+            quote!(::clay::BorderStyle { width: #width, color: #color })
+        }
+        _ => quote!(#value),
+    }
+}
+
+// rgb(r, g, b) and rgba(r, g, b, a) with any numeric components
+fn color(value: &Expr) -> Option<TokenStream> {
+    let Expr::Call(ExprCall { func, args, .. }) = value else {
+        return None;
+    };
+    let function = path_ident(func)?;
+    let args = args.iter();
+    match (function.to_string().as_str(), args.len()) {
+        ("rgb", 3) => Some(quote!(::clay::Color::rgb(#((#args) as f32),*))),
+        ("rgba", 4) => Some(quote!(::clay::Color::rgba(#((#args) as f32),*))),
+        _ => None,
+    }
+}
+
+// fixed(n), grow, grow(min, max), fit, fit(min, max) or percent(p)
+fn sizing_axis(value: &Expr) -> TokenStream {
+    let (function, args) = match value {
+        Expr::Call(ExprCall { func, args, .. }) => match path_ident(func) {
+            Some(function) => (function, args.iter().collect::<Vec<_>>()),
+            None => return quote!(#value),
+        },
+        Expr::Path(_) => match path_ident(value) {
+            Some(function) => (function, Vec::new()),
+            None => return quote!(#value),
+        },
+        _ => return quote!(#value),
+    };
+    match (function.to_string().as_str(), args.as_slice()) {
+        ("fixed", [size]) => quote!(::clay::SizingAxis::fixed((#size) as f32)),
+        ("percent", [percent]) => quote!(::clay::SizingAxis::percent((#percent) as f32)),
+        ("grow", []) => quote!(::clay::SizingAxis::grow_unbounded()),
+        ("fit", []) => quote!(::clay::SizingAxis::fit_unbounded()),
+        ("grow" | "fit", [min, max]) => {
+            quote!(::clay::SizingAxis::#function((#min) as f32, (#max) as f32))
+        }
+        _ => quote!(#value),
+    }
+}
+
+fn path_ident(expr: &Expr) -> Option<&Ident> {
+    match expr {
+        Expr::Path(ExprPath {
+            path, qself: None, ..
+        }) => path.get_ident(),
+        _ => None,
+    }
+}
+
+// text!("File", font_id, size = 16) or text!("{count} items", ..) inside children
+struct TextArgs {
+    text: Expr,
+    fields: Vec<(Ident, Expr)>,
+}
+
+impl Parse for TextArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let text = input.parse()?;
+        let mut fields = Vec::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            let name: Ident = input.parse()?;
+            if input.is_empty() || input.peek(Token![,]) {
+                fields.push((name.clone(), syn::parse_quote!(#name)));
+                continue;
+            }
+            input.parse::<Token![=]>()?;
+            fields.push((name, input.parse()?));
+        }
+        Ok(Self { text, fields })
+    }
+}
+
+struct TextRewriter<'a> {
+    builder: &'a Ident,
+}
+
+impl TextRewriter<'_> {
+    // Only the bare text! path, clay::text! formats a string rather than attaching an element
+    fn expand_text(&mut self, mac: &Macro) -> Option<Expr> {
+        if !mac.path.is_ident("text") {
+            return None;
+        }
+        let args = match mac.parse_body::<TextArgs>() {
+            Ok(args) => args,
+            Err(error) => {
+                let error = syn::Error::new(
+                    error.span(),
+                    format!(
+                        "{error}, expected text!(\"text\", field = value, ..), \
+                         use clay::text! to format a string"
+                    ),
+                )
+                .to_compile_error();
+                return Some(syn::parse_quote!({ #error }));
+            }
+        };
+        let builder = self.builder;
+        let text = &args.text;
+        let fields = args
+            .fields
+            .iter()
+            .map(|(name, value)| {
+                let field = match name.to_string().as_str() {
+                    "size" => Ident::new("font_size", name.span()),
+                    "color" => Ident::new("text_color", name.span()),
+                    _ => name.clone(),
+                };
+                (field, value)
+            })
+            .collect::<Vec<_>>();
+        if let Some(error) = duplicate_field(fields.iter().map(|(field, _)| field)) {
+            return Some(syn::parse_quote!({ #error }));
+        }
+        let fields = fields.iter().map(|(field, value)| {
+            let value = field_value(field, value);
+            quote!(#field: #value)
+        });
+        // Literals with placeholders are formatted into the arena, text!("{count} items")
+        let text = match text {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(lit), ..
+            }) if lit.value().contains(['{', '}']) => {
+                quote!(config.with_owned(::std::format!(#lit)))
+            }
+            _ => quote!(config.with((#text).into())),
+        };
+        Some(syn::parse_quote_spanned! {mac.path.span()=>
+            {
+                #[allow(clippy::needless_update, clippy::redundant_field_names)]
+                let config = ::clay::Text { #(#fields,)* ..::clay::default() };
+                ::clay::Element::attach(&#text, #builder)
+            }
+        })
+    }
+}
+
+impl VisitMut for TextRewriter<'_> {
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        if let Stmt::Macro(stmt_macro) = stmt {
+            if let Some(expr) = self.expand_text(&stmt_macro.mac) {
+                *stmt = Stmt::Expr(expr, Some(Default::default()));
+                return;
+            }
+        }
+        visit_mut::visit_stmt_mut(self, stmt);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Macro(expr_macro) = expr {
+            if let Some(text) = self.expand_text(&expr_macro.mac) {
+                *expr = text;
+                return;
+            }
+        }
+        visit_mut::visit_expr_mut(self, expr);
+    }
+}
@@ -2,6 +2,8 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, ItemEnum};
 
+mod clay;
+
 #[proc_macro_attribute]
 pub fn packed_enum(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // Parse the input enum
@@ -30,3 +32,10 @@ pub fn packed_enum(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
     generated_enum.into()
 }
+
+// Expands to Builder::build and Element::attach calls, see clay.rs for the syntax
+#[proc_macro]
+pub fn clay(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as clay::ClayInput);
+    clay::expand(input).into()
+}
@@ -0,0 +1,6 @@
+#[test]
+fn ui() {
+    let tests = trybuild::TestCases::new();
+    tests.pass("tests/ui/pass_*.rs");
+    tests.compile_fail("tests/ui/fail_*.rs");
+}
@@ -0,0 +1,12 @@
+use clay::prelude::*;
+
+fn main() {
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.layout(dimensions, |builder| {
+        let count = 2;
+        clay::clay!(builder => {
+            clay::Text::new().with(text!(builder, "{} formatted", count)).attach(builder);
+        })
+    });
+}
@@ -0,0 +1,14 @@
+fn main() {
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.layout(dimensions, |builder| {
+        clay::clay!(
+            builder,
+            layout {
+                padding: (16, 8),
+                child_gap: 4,
+                padding: (8, 8)
+            }
+        )
+    });
+}
@@ -0,0 +1,12 @@
+fn main() {
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.layout(dimensions, |builder| {
+        clay::clay!(
+            builder,
+            layout {
+                direction: clay::LayoutDirection::TopToBottom
+            }
+        )
+    });
+}
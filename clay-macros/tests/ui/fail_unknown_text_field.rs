@@ -0,0 +1,8 @@
+fn main() {
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.layout(
+        dimensions,
+        |builder| clay::clay!(builder => { text!("Item", size = 10, weight = 700); }),
+    );
+}
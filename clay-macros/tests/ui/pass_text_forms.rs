@@ -0,0 +1,16 @@
+use clay::prelude::*;
+
+fn main() {
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.set_measure_text_callback(|text: &str, config: &clay::Text| {
+        clay::Dimensions::new(text.len() as f32 * 5.0, config.font_size as f32)
+    });
+    arena.layout(dimensions, |builder| {
+        let count = 2;
+        clay::clay!(builder, layout { layout_direction: clay::LayoutDirection::TopToBottom } => {
+            clay::Text::new().with(clay::text!(builder, "{} formatted", count)).attach(builder);
+            text!("{count} in clay!", size = 10, color = rgb(255, 255, 255));
+        })
+    });
+}
@@ -0,0 +1,325 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Write},
+    path::Path,
+};
+
+// Renders each frame as absolutely positioned HTML elements
+pub struct HtmlRenderer {
+    dimensions: clay::Dimensions,
+    // Font families indexed by clay::Text::font_id
+    font_families: RefCell<Vec<String>>,
+    images: clay::Images<Image>,
+    fragment: RefCell<String>,
+}
+
+// An <img> src, added with clay::ImageRegistry::add_image
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub src: String,
+    // Reported as the clay::Image source_dimensions if known
+    pub dimensions: Option<clay::Dimensions>,
+}
+
+impl Image {
+    pub fn new(src: impl Into<String>) -> Self {
+        Self {
+            src: src.into(),
+            dimensions: None,
+        }
+    }
+
+    pub fn with_dimensions(src: impl Into<String>, width: f32, height: f32) -> Self {
+        Self {
+            src: src.into(),
+            dimensions: Some(clay::Dimensions::new(width, height)),
+        }
+    }
+}
+
+impl HtmlRenderer {
+    pub fn new(dimensions: clay::Dimensions) -> Self {
+        Self {
+            dimensions,
+            font_families: RefCell::new(Vec::new()),
+            images: clay::Images::new(),
+            fragment: RefCell::new(String::new()),
+        }
+    }
+
+    pub fn add_font_family(&self, family: &str) -> u16 {
+        let mut font_families = self.font_families.borrow_mut();
+        font_families.push(family.to_owned());
+        (font_families.len() - 1) as u16
+    }
+
+    // The last rendered frame as a relatively positioned <div>, for embedding in a page
+    pub fn fragment(&self) -> String {
+        self.fragment.borrow().clone()
+    }
+
+    // The last rendered frame as a standalone page
+    pub fn document(&self) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n</head>\n<body style=\"margin:0\">\n{}</body>\n</html>\n",
+            self.fragment.borrow()
+        )
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.document())
+    }
+
+    fn write_fragment(
+        &self,
+        html: &mut String,
+        render_commands: &mut clay::RenderCommandIter<'_>,
+    ) -> fmt::Result {
+        writeln!(
+            html,
+            r#"<div style="position:relative;overflow:hidden;width:{}px;height:{}px">"#,
+            self.dimensions.width, self.dimensions.height
+        )?;
+        // Positions are relative to the innermost scissor wrapper
+        let mut origins = vec![(0., 0.)];
+        for command in render_commands {
+            let &(origin_x, origin_y) = origins.last().unwrap();
+            let bounding_box = clay::BoundingBox {
+                x: command.bounding_box.x - origin_x,
+                y: command.bounding_box.y - origin_y,
+                ..command.bounding_box
+            };
+            let indent = "  ".repeat(origins.len());
+            match command.element() {
+                clay::RenderCommandElement::Rectangle(rectangle) => {
+                    writeln!(
+                        html,
+                        r#"{indent}<div style="{}{}background-color:{}"></div>"#,
+                        Position(&bounding_box),
+                        BorderRadius(&rectangle.corner_radius),
+                        Rgba(rectangle.color),
+                    )?;
+                }
+                clay::RenderCommandElement::Border(border) => {
+                    write!(
+                        html,
+                        r#"{indent}<div style="{}{}box-sizing:border-box"#,
+                        Position(&bounding_box),
+                        BorderRadius(&border.corner_radius),
+                    )?;
+                    for (side, style) in [
+                        ("left", &border.left),
+                        ("right", &border.right),
+                        ("top", &border.top),
+                        ("bottom", &border.bottom),
+                    ] {
+                        if style.width > 0 {
+                            write!(
+                                html,
+                                ";border-{side}:{}px solid {}",
+                                style.width,
+                                Rgba(style.color)
+                            )?;
+                        }
+                    }
+                    writeln!(html, r#""></div>"#)?;
+                }
+                clay::RenderCommandElement::Text(text) => {
+                    let font_families = self.font_families.borrow();
+                    let family = font_families
+                        .get(text.font_id as usize)
+                        .map_or("sans-serif", String::as_str);
+                    write!(
+                        html,
+                        r#"{indent}<span style="{}white-space:pre;font-family:{};font-size:{}px;line-height:{}px;color:{}"#,
+                        Position(&bounding_box),
+                        Escape(family),
+                        text.font_size,
+                        bounding_box.height,
+                        Rgba(text.text_color),
+                    )?;
+                    if text.letter_spacing > 0 {
+                        write!(html, ";letter-spacing:{}px", text.letter_spacing)?;
+                    }
+                    writeln!(html, r#"">{}</span>"#, Escape(command.text.into()))?;
+                }
+                clay::RenderCommandElement::Image(image) => {
+                    if let Some(image) = self.images.get(image.handle) {
+                        writeln!(
+                            html,
+                            r#"{indent}<img style="{}" src="{}">"#,
+                            Position(&bounding_box),
+                            Escape(&image.src),
+                        )?;
+                    }
+                }
+                clay::RenderCommandElement::ScissorStart => {
+                    writeln!(
+                        html,
+                        r#"{indent}<div style="{}overflow:hidden">"#,
+                        Position(&bounding_box)
+                    )?;
+                    origins.push((command.bounding_box.x, command.bounding_box.y));
+                }
+                clay::RenderCommandElement::ScissorEnd if origins.len() > 1 => {
+                    origins.pop();
+                    writeln!(html, "{}</div>", "  ".repeat(origins.len()))?;
+                }
+                _ => {}
+            }
+        }
+        for depth in (1..origins.len()).rev() {
+            writeln!(html, "{}</div>", "  ".repeat(depth))?;
+        }
+        writeln!(html, "</div>")
+    }
+}
+
+impl clay::ImageRegistry for HtmlRenderer {
+    type Image = Image;
+
+    fn images(&self) -> &clay::Images<Image> {
+        &self.images
+    }
+
+    fn dimensions(image: &Image) -> Option<clay::Dimensions> {
+        image.dimensions
+    }
+}
+
+impl clay::Renderer for HtmlRenderer {
+    fn prepare_frame(&self) -> clay::Dimensions {
+        self.dimensions
+    }
+
+    fn render(&self, render_commands: &mut clay::RenderCommandIter<'_>) {
+        let mut html = String::new();
+        // Writing to a String can't fail
+        self.write_fragment(&mut html, render_commands).unwrap();
+        *self.fragment.borrow_mut() = html;
+    }
+}
+
+struct Position<'a>(&'a clay::BoundingBox);
+
+impl fmt::Display for Position<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "position:absolute;left:{}px;top:{}px;width:{}px;height:{}px;",
+            self.0.x, self.0.y, self.0.width, self.0.height
+        )
+    }
+}
+
+struct BorderRadius<'a>(&'a clay::CornerRadius);
+
+impl fmt::Display for BorderRadius<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let radius = self.0;
+        if radius.top_left == 0.
+            && radius.top_right == 0.
+            && radius.bottom_right == 0.
+            && radius.bottom_left == 0.
+        {
+            return Ok(());
+        }
+        write!(
+            f,
+            "border-radius:{}px {}px {}px {}px;",
+            radius.top_left, radius.top_right, radius.bottom_right, radius.bottom_left
+        )
+    }
+}
+
+struct Rgba(clay::Color);
+
+impl fmt::Display for Rgba {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rgba({},{},{},{})",
+            self.0.r.round(),
+            self.0.g.round(),
+            self.0.b.round(),
+            self.0.a / 255.
+        )
+    }
+}
+
+struct Escape<'a>(&'a str);
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clay::prelude::*;
+
+    #[test]
+    fn render_html_fragment() {
+        let dimensions = clay::Dimensions::new(100., 100.);
+        let renderer = HtmlRenderer::new(dimensions);
+        let font_id = renderer.add_font_family("Roboto");
+        let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+        arena.set_measure_text_callback(|text: &str, config: &clay::Text| clay::Dimensions {
+            width: (text.len() * config.font_size as usize) as f32,
+            height: config.font_size as f32,
+        });
+        arena.render(&renderer, |builder| {
+            builder.build(
+                |builder| {
+                    clay::Layout {
+                        sizing: clay::Sizing {
+                            width: clay::SizingAxis::fixed(50.),
+                            height: clay::SizingAxis::fixed(50.),
+                        },
+                        padding: clay::Padding { x: 10, y: 10 },
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                    clay::Scroll {
+                        horizontal: false,
+                        vertical: true,
+                    }
+                    .attach(builder);
+                    clay::Border::all_radius(2, clay::Color::rgb(0., 0., 255.), 4.).attach(builder);
+                },
+                |builder| {
+                    clay::Text {
+                        font_id,
+                        font_size: 10,
+                        text_color: clay::Color::rgb(255., 0., 0.),
+                        ..clay::default()
+                    }
+                    .with("a<b".into())
+                    .attach(builder);
+                },
+            )
+        });
+
+        assert_eq!(
+            renderer.fragment(),
+            r#"<div style="position:relative;overflow:hidden;width:100px;height:100px">
+  <div style="position:absolute;left:0px;top:0px;width:50px;height:50px;overflow:hidden">
+    <span style="position:absolute;left:10px;top:10px;width:30px;height:10px;white-space:pre;font-family:Roboto;font-size:10px;line-height:10px;color:rgba(255,0,0,1)">a&lt;b</span>
+    <div style="position:absolute;left:0px;top:0px;width:50px;height:50px;border-radius:4px 4px 4px 4px;box-sizing:border-box;border-left:2px solid rgba(0,0,255,1);border-right:2px solid rgba(0,0,255,1);border-top:2px solid rgba(0,0,255,1);border-bottom:2px solid rgba(0,0,255,1)"></div>
+  </div>
+</div>
+"#
+        );
+    }
+}
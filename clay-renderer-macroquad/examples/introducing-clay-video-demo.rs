@@ -35,24 +35,17 @@ async fn main() {
         Document{ title: "Article 5".into(), contents: "Article 5".into() },
     ];
 
-    let size: u32 = clay::Arena::min_memory_size();
-    let memory = vec![0u8; size as usize];
-    let arena = clay::Arena::new(&memory);
-    arena.initialize(WINDOW_SIZE, d());
+    let mut arena = clay::Arena::with_capacity(WINDOW_SIZE, d());
     let font = load_ttf_font(
         "clay/clay/examples/introducing-clay-video-demo/resources/Roboto-Regular.ttf",
     )
     .await
     .unwrap();
-    let font_id_body_16 = clay_renderer_macroquad::add_font(font);
-
-    let mut arena = arena;
     let renderer = clay_renderer_macroquad::MacroquadRenderer::new();
+    let font_id_body_16 = renderer.add_font(font);
+    arena.set_measure_text_callback(renderer.text_measurer());
 
-    let layout_expand = clay::Sizing {
-        width: clay::SizingAxis::grow(0., f32::MAX),
-        height: clay::SizingAxis::grow(0., f32::MAX),
-    };
+    let layout_expand = clay::Sizing::expand();
     let content_background = clay::Rectangle {
         color: clay::Color::rgb(90., 90., 90.),
         corner_radius: clay::CornerRadius::new(8.),
@@ -105,26 +98,18 @@ async fn main() {
     };
 
     let current_selected_document_index = &Cell::new(0);
-    // XXX can't get closures working...
-    /*
-    let mut hover_callback = 
+    let hover_callback = 
     |element_id: clay::ElementId, pointer_data: clay::PointerData| {
         if pointer_data.state == clay::PointerDataInteractionState::PressedThisFrame {
-            current_selected_document_index .set( element_id.offset() as usize);
+            current_selected_document_index.set(element_id.offset() as usize);
         }
     };
-    */
-    unsafe  extern "C"  fn hover_callback_raw(element_id: clay::ElementId, pointer_data: clay::PointerData, user_data: isize) {
-        if pointer_data.state == clay::PointerDataInteractionState::PressedThisFrame {
-            unsafe {
-                let ptr = user_data as *const &Cell<usize>;
-                assert!(!ptr.is_null());
-                let current_selected_document_index: &Cell<usize> = *ptr;
-                current_selected_document_index .set( element_id.offset() as usize);
-            }
-        }
-    }
+    let mut debug_mode = false;
     loop {
+        if is_key_pressed(KeyCode::D) {
+            debug_mode = !debug_mode;
+            arena.set_debug_mode(debug_mode);
+        }
         arena.render(&renderer, |builder| {
             builder.build(
                 |builder| {
@@ -330,9 +315,8 @@ async fn main() {
                                                     }
                                                     .attach(builder);
                                                 } else {
-                                                    // builder.set_on_hover_callback(&mut hover_callback);
-                                                    builder.set_on_hover_callback_raw(hover_callback_raw, &current_selected_document_index);
-                                                
+                                                    builder.set_on_hover_callback(hover_callback);
+
                                                     if clay::Builder::is_hovered() {
                                                         clay::Rectangle {
                                                             color: clay::Color::rgba(
@@ -1,4 +1,3 @@
-use clay::prelude::*;
 use macroquad::prelude::*;
 
 #[macroquad::main("Simple")]
@@ -8,38 +7,27 @@ async fn main() {
     )
     .await
     .unwrap();
-    let font_id = clay_renderer_macroquad::add_font(font);
+    let renderer = clay_renderer_macroquad::MacroquadRenderer::new();
+    let font_id = renderer.add_font(font);
 
-    let size: u32 = clay::Arena::min_memory_size();
-    let memory = vec![0u8; size as usize];
-    let arena = clay::Arena::new(&memory);
     let dimensions = clay::Dimensions::new(300.0, 300.0);
-    arena.initialize(dimensions, clay::default());
-    let mut arena = arena;
-    let renderer = clay_renderer_macroquad::MacroquadRenderer::new();
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.set_measure_text_callback(renderer.text_measurer());
     loop {
         arena.render(&renderer, |builder| {
-            builder.build(
-                |builder| {
-                    clay::Layout {
-                        layout_direction: clay::LayoutDirection::TopToBottom,
-                        padding: clay::Padding { x: 16, y: 16 },
-                        child_gap: 16,
-                        ..clay::default()
-                    }
-                    .attach(builder);
-
-                    clay::Rectangle {
-                        color: clay::Color::rgb(255., 0., 0.),
-                        ..clay::default()
-                    }
-                    .attach(builder);
+            clay::clay!(
+                builder,
+                layout {
+                    layout_direction: clay::LayoutDirection::TopToBottom,
+                    padding: (16, 16),
+                    child_gap: 16,
                 },
-                |builder| {
+                rectangle { color: rgb(255, 0, 0) }
+                => {
                     child_rect(builder, clay::Color::rgb(0., 255., 0.), font_id);
                     child_rect(builder, clay::Color::rgb(0., 0., 255.), font_id);
                     child_rect(builder, clay::Color::rgb(255., 0., 255.), font_id);
-                },
+                }
             )
         });
 
@@ -48,33 +36,15 @@ async fn main() {
 }
 
 fn child_rect(builder: &clay::Builder, color: clay::Color, font_id: u16) {
-    builder.build(
-        |builder| {
-            clay::Layout {
-                padding: clay::Padding { x: 16, y: 16 },
-                sizing: clay::Sizing {
-                    height: clay::SizingAxis::fixed(80.),
-                    ..clay::default()
-                },
-                ..clay::default()
-            }
-            .attach(builder);
-
-            clay::Rectangle {
-                color,
-                ..clay::default()
-            }
-            .attach(builder);
-        },
-        |builder| {
-            clay::Text {
-                font_id,
-                font_size: 18,
-                text_color: clay::Color::rgb(0., 0., 0.),
-                ..clay::default()
-            }
-            .with("Foobar".into())
-            .attach(builder);
+    clay::clay!(
+        builder,
+        layout {
+            padding: (16, 16),
+            sizing: (fit, fixed(80)),
         },
+        rectangle { color }
+        => {
+            text!("Foobar", font_id, size = 18, color = rgb(0, 0, 0));
+        }
     );
 }
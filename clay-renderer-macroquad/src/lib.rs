@@ -1,59 +1,473 @@
+use macroquad::miniquad::{window, EventHandler, KeyMods};
 use macroquad::prelude::*;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
-// Clay_SetMeasureTextFunction takes no userdata, so this needs to be global
-static mut FONTS: Vec<macroquad::text::Font> = Vec::new();
+pub struct MacroquadRenderer {
+    // Shared with the text measurer registered on the arena
+    fonts: Rc<RefCell<Vec<macroquad::text::Font>>>,
+    images: clay::Images<Texture2D>,
+    custom_renderer: RefCell<Option<CustomRenderer>>,
+    // Registered on the first frame, macroquad isn't running before that
+    input_subscriber: Cell<Option<usize>>,
+}
+
+// Draws clay::Custom elements, the payload is available from RenderCommand::custom
+type CustomRenderer = Box<dyn FnMut(&clay::RenderCommand)>;
+
+impl MacroquadRenderer {
+    pub fn new() -> Self {
+        Self {
+            fonts: Rc::new(RefCell::new(Vec::new())),
+            images: clay::Images::new(),
+            custom_renderer: RefCell::new(None),
+            input_subscriber: Cell::new(None),
+        }
+    }
+
+    pub fn set_custom_renderer<F>(&self, custom_renderer: F)
+    where
+        F: FnMut(&clay::RenderCommand) + 'static,
+    {
+        *self.custom_renderer.borrow_mut() = Some(Box::new(custom_renderer));
+    }
+
+    pub fn add_font(&self, font: macroquad::text::Font) -> u16 {
+        let mut fonts = self.fonts.borrow_mut();
+        fonts.push(font);
+        (fonts.len() - 1) as u16
+    }
 
-pub fn add_font(font: macroquad::text::Font) -> u16 {
-    unsafe {
-        FONTS.push(font);
-        (FONTS.len() - 1) as u16
+    // Register with clay::Arena::set_measure_text_callback
+    pub fn text_measurer(&self) -> impl clay::TextMeasurer + 'static {
+        let fonts = Rc::clone(&self.fonts);
+        move |text: &str, config: &clay::Text| {
+            let fonts = fonts.borrow();
+            // Text in a font that was never added takes no space and isn't drawn
+            let Some(font) = fonts.get(config.font_id as usize) else {
+                return clay::Dimensions::default();
+            };
+            let size = macroquad::text::measure_text(text, Some(font), config.font_size, 1.0);
+            clay::Dimensions {
+                width: size.width,
+                height: size.height,
+            }
+        }
     }
 }
 
-fn get_font(font_id: u16) -> &'static macroquad::text::Font {
-    unsafe { &FONTS[font_id as usize] }
+impl Default for MacroquadRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-extern "C" fn measure_text(text: &clay::String, config: &clay::Text) -> clay::Dimensions {
-    let font = unsafe { &FONTS[config.font_id as usize] };
-    let size = macroquad::text::measure_text((*text).into(), Some(font), config.font_size, 1.0);
-    clay::Dimensions {
-        width: size.width,
-        height: size.height,
+impl clay::ImageRegistry for MacroquadRenderer {
+    type Image = Texture2D;
+
+    fn images(&self) -> &clay::Images<Texture2D> {
+        &self.images
     }
+
+    fn dimensions(texture: &Texture2D) -> Option<clay::Dimensions> {
+        Some(clay::Dimensions::new(texture.width(), texture.height()))
+    }
+}
+
+// Scales the image to fit the bounding box, centered and keeping the aspect ratio
+// of source_dimensions, or of the texture if they aren't set
+fn draw_image(texture: &Texture2D, bounding_box: &clay::BoundingBox, image: &clay::Image) {
+    let (source_width, source_height) =
+        if image.source_dimensions.width > 0. && image.source_dimensions.height > 0. {
+            (
+                image.source_dimensions.width,
+                image.source_dimensions.height,
+            )
+        } else {
+            (texture.width(), texture.height())
+        };
+    let scale = f32::min(
+        bounding_box.width / source_width.max(1.),
+        bounding_box.height / source_height.max(1.),
+    );
+    let (width, height) = (source_width * scale, source_height * scale);
+    draw_texture_ex(
+        texture,
+        bounding_box.x + (bounding_box.width - width) / 2.,
+        bounding_box.y + (bounding_box.height - height) / 2.,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Some(vec2(width, height)),
+            ..Default::default()
+        },
+    );
 }
 
-pub struct MacroquadRenderer(());
+// Points along a quarter circle, about 2px apart
+fn corner_segments(radius: f32) -> usize {
+    ((radius * std::f32::consts::FRAC_PI_2 / 2.).ceil() as usize).clamp(1, 64)
+}
 
-impl MacroquadRenderer {
-    pub fn new() -> Self {
-        clay::Arena::set_measure_text_callback(measure_text);
-        Self(())
-    }
-}
-
-// Emulate raylib draw_ring using draw_arc
-#[allow(clippy::too_many_arguments)]
-fn draw_ring(
-    x: f32,
-    y: f32,
-    inner_radius: f32,
-    outer_radius: f32,
-    start_angle: f32,
-    end_angle: f32,
-    segments: u8,
-    color: clay::Color,
+// Clockwise outline of a rounded rectangle, radii are clamped to half the shorter side
+fn rounded_rectangle_outline(
+    bounding_box: &clay::BoundingBox,
+    corner_radius: &clay::CornerRadius,
+) -> Vec<Vec2> {
+    let clay::BoundingBox {
+        x,
+        y,
+        width,
+        height,
+    } = *bounding_box;
+    let max_radius = width.min(height) / 2.;
+    // Corner, direction of its arc's center and start angle
+    let corners = [
+        (corner_radius.top_left, vec2(x, y), vec2(1., 1.), 180.),
+        (
+            corner_radius.top_right,
+            vec2(x + width, y),
+            vec2(-1., 1.),
+            270.,
+        ),
+        (
+            corner_radius.bottom_right,
+            vec2(x + width, y + height),
+            vec2(-1., -1.),
+            0.,
+        ),
+        (
+            corner_radius.bottom_left,
+            vec2(x, y + height),
+            vec2(1., -1.),
+            90.,
+        ),
+    ];
+    let mut outline = Vec::new();
+    for (radius, corner, direction, start_angle) in corners {
+        let radius = radius.clamp(0., max_radius);
+        let center = corner + direction * radius;
+        if radius <= 0. {
+            outline.push(center);
+            continue;
+        }
+        let segments = corner_segments(radius);
+        for segment in 0..=segments {
+            let angle: f32 = start_angle + 90. * segment as f32 / segments as f32;
+            outline.push(center + radius * Vec2::from_angle(angle.to_radians()));
+        }
+    }
+    outline
+}
+
+fn draw_rounded_rectangle(
+    bounding_box: &clay::BoundingBox,
+    corner_radius: &clay::CornerRadius,
+    color: macroquad::color::Color,
 ) {
-    draw_arc(
+    if *corner_radius == clay::CornerRadius::default() {
+        draw_rectangle(
+            bounding_box.x,
+            bounding_box.y,
+            bounding_box.width,
+            bounding_box.height,
+            color,
+        );
+        return;
+    }
+    draw_mesh(&rounded_rectangle_mesh(bounding_box, corner_radius, color));
+}
+
+// A convex polygon as a triangle fan around its center, so translucent colors
+// don't overlap
+fn rounded_rectangle_mesh(
+    bounding_box: &clay::BoundingBox,
+    corner_radius: &clay::CornerRadius,
+    color: macroquad::color::Color,
+) -> Mesh {
+    let outline = rounded_rectangle_outline(bounding_box, corner_radius);
+    let center = vec2(
+        bounding_box.x + bounding_box.width / 2.,
+        bounding_box.y + bounding_box.height / 2.,
+    );
+    let mut vertices = vec![Vertex::new(center.x, center.y, 0., 0., 0., color)];
+    vertices.extend(
+        outline
+            .iter()
+            .map(|point| Vertex::new(point.x, point.y, 0., 0., 0., color)),
+    );
+    let count = outline.len() as u16;
+    let indices = (0..count)
+        .flat_map(|i| [0, i + 1, (i + 1) % count + 1])
+        .collect();
+    Mesh {
+        vertices,
+        indices,
+        texture: None,
+    }
+}
+
+// Scissor rectangles are in framebuffer pixels
+type Scissor = (i32, i32, i32, i32);
+
+// scale is the screen's DPI scale, layout is in logical pixels
+fn scissor_rect(bounding_box: &clay::BoundingBox, parent: Option<Scissor>, scale: f32) -> Scissor {
+    let mut x0 = (bounding_box.x * scale).floor() as i32;
+    let mut y0 = (bounding_box.y * scale).floor() as i32;
+    let mut x1 = ((bounding_box.x + bounding_box.width) * scale).ceil() as i32;
+    let mut y1 = ((bounding_box.y + bounding_box.height) * scale).ceil() as i32;
+    if let Some((x, y, width, height)) = parent {
+        x0 = x0.max(x);
+        y0 = y0.max(y);
+        x1 = x1.min(x + width);
+        y1 = y1.min(y + height);
+    }
+    (x0, y0, (x1 - x0).max(0), (y1 - y0).max(0))
+}
+
+fn set_scissor(scissor: Option<Scissor>) {
+    unsafe { get_internal_gl() }.quad_gl.scissor(scissor);
+}
+
+fn lerp_color(
+    from: macroquad::color::Color,
+    to: macroquad::color::Color,
+    t: f32,
+) -> macroquad::color::Color {
+    macroquad::color::Color {
+        r: from.r + (to.r - from.r) * t,
+        g: from.g + (to.g - from.g) * t,
+        b: from.b + (to.b - from.b) * t,
+        a: from.a + (to.a - from.a) * t,
+    }
+}
+
+// The border is one ring between the outer rounded rectangle and an inner one inset by
+// each side's width. Corners blend from one side's color to the next, and rounded
+// borders get a transparent fringe on both edges for anti-aliasing
+fn draw_border(bounding_box: &clay::BoundingBox, border: &clay::Border) {
+    draw_mesh(&border_mesh(bounding_box, border));
+}
+
+// Four vertices per point around the ring: outer fringe, outer, inner, inner fringe
+fn border_mesh(bounding_box: &clay::BoundingBox, border: &clay::Border) -> Mesh {
+    const FRINGE: f32 = 1.;
+    let clay::BoundingBox {
         x,
         y,
-        segments,                            // sides: use segments directly
-        (inner_radius + outer_radius) / 2.0, // radius: use average of inner/outer as center radius
-        start_angle,                         // rotation: use start_angle as base rotation
-        outer_radius - inner_radius,         // thickness: difference between outer and inner radius
-        end_angle - start_angle,             // arc: angle difference
-        Color(color).into(),
+        width,
+        height,
+    } = *bounding_box;
+    let side = |style: &clay::BorderStyle| {
+        let mut color: macroquad::color::Color = Color(style.color).into();
+        // Fade corners out towards sides without a border
+        if style.width == 0 {
+            color.a = 0.;
+        }
+        (style.width as f32, color)
+    };
+    let (left, right, top, bottom) = (
+        side(&border.left),
+        side(&border.right),
+        side(&border.top),
+        side(&border.bottom),
     );
+    let radius = &border.corner_radius;
+    let max_radius = width.min(height) / 2.;
+    // Clockwise from the top left: radius, corner, direction towards the center,
+    // start angle, then the side before and after the corner
+    let corners = [
+        (radius.top_left, vec2(x, y), vec2(1., 1.), 180., left, top),
+        (
+            radius.top_right,
+            vec2(x + width, y),
+            vec2(-1., 1.),
+            270.,
+            top,
+            right,
+        ),
+        (
+            radius.bottom_right,
+            vec2(x + width, y + height),
+            vec2(-1., -1.),
+            0.,
+            right,
+            bottom,
+        ),
+        (
+            radius.bottom_left,
+            vec2(x, y + height),
+            vec2(1., -1.),
+            90.,
+            bottom,
+            left,
+        ),
+    ];
+    let fringe = if *radius == clay::CornerRadius::default() {
+        0.
+    } else {
+        FRINGE
+    };
+
+    let mut vertices = Vec::new();
+    for (i, (radius, corner, direction, start_angle, before, after)) in
+        corners.into_iter().enumerate()
+    {
+        // Left and right sides are the horizontal widths
+        let side_widths = if i % 2 == 0 {
+            vec2(before.0, after.0)
+        } else {
+            vec2(after.0, before.0)
+        };
+        let radius = radius.clamp(0., max_radius);
+        let outer_center = corner + direction * radius;
+        let inner_radius = (vec2(radius, radius) - side_widths).max(Vec2::ZERO);
+        let inner_center = corner + direction * (side_widths + inner_radius);
+        // Sharp corners get two points so each side keeps its own color
+        let segments = if radius > 0. {
+            corner_segments(radius)
+        } else {
+            1
+        };
+        for segment in 0..=segments {
+            let t = segment as f32 / segments as f32;
+            let angle: f32 = start_angle + 90. * t;
+            let normal = Vec2::from_angle(angle.to_radians());
+            let outer = outer_center + normal * radius;
+            let inner = inner_center + normal * inner_radius;
+            let color = lerp_color(before.1, after.1, t);
+            let clear = macroquad::color::Color { a: 0., ..color };
+            for (point, color) in [
+                (outer + normal * fringe, clear),
+                (outer, color),
+                (inner, color),
+                (inner - normal * fringe, clear),
+            ] {
+                vertices.push(Vertex::new(point.x, point.y, 0., 0., 0., color));
+            }
+        }
+    }
+
+    let count = vertices.len() as u16 / 4;
+    let strips = if fringe > 0. { 0..3 } else { 1..2 };
+    let mut indices = Vec::new();
+    for i in 0..count {
+        let j = (i + 1) % count;
+        for strip in strips.clone() {
+            let (a, b) = (i * 4 + strip, j * 4 + strip);
+            indices.extend([a, a + 1, b, b, a + 1, b + 1]);
+        }
+    }
+    Mesh {
+        vertices,
+        indices,
+        texture: None,
+    }
+}
+
+// The system clipboard, for clay::Arena::set_clipboard
+#[derive(Debug, Default)]
+pub struct MacroquadClipboard;
+
+impl clay::Clipboard for MacroquadClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        window::clipboard_get()
+    }
+    fn set_text(&mut self, text: &str) {
+        window::clipboard_set(text);
+    }
+}
+
+// Collects the key presses and typed text since the last frame
+struct KeyEvents(Vec<clay::KeyEvent>);
+
+impl EventHandler for KeyEvents {
+    fn update(&mut self) {}
+    fn draw(&mut self) {}
+
+    fn key_down_event(&mut self, keycode: KeyCode, keymods: KeyMods, _repeat: bool) {
+        let key = match keycode {
+            KeyCode::Tab => clay::Key::Tab,
+            KeyCode::Enter | KeyCode::KpEnter => clay::Key::Enter,
+            KeyCode::Escape => clay::Key::Escape,
+            KeyCode::Backspace => clay::Key::Backspace,
+            KeyCode::Delete => clay::Key::Delete,
+            KeyCode::Left => clay::Key::Left,
+            KeyCode::Right => clay::Key::Right,
+            KeyCode::Up => clay::Key::Up,
+            KeyCode::Down => clay::Key::Down,
+            KeyCode::Home => clay::Key::Home,
+            KeyCode::End => clay::Key::End,
+            KeyCode::PageUp => clay::Key::PageUp,
+            KeyCode::PageDown => clay::Key::PageDown,
+            KeyCode::Space => clay::Key::Space,
+            keycode => match key_character(keycode) {
+                Some(c) => clay::Key::Character(c),
+                None => return,
+            },
+        };
+        self.0.push(clay::KeyEvent::Key {
+            key,
+            modifiers: clay::Modifiers {
+                shift: keymods.shift,
+                control: keymods.ctrl,
+                alt: keymods.alt,
+                logo: keymods.logo,
+            },
+        });
+    }
+
+    fn char_event(&mut self, character: char, keymods: KeyMods, _repeat: bool) {
+        // Shortcuts arrive as KeyEvent::Key, AltGr is reported as Control+Alt on Windows
+        let shortcut = (keymods.ctrl && !keymods.alt) || keymods.logo;
+        if !character.is_control() && !shortcut {
+            self.0.push(clay::KeyEvent::Text(character));
+        }
+    }
+}
+
+fn key_character(keycode: KeyCode) -> Option<char> {
+    let c = match keycode {
+        KeyCode::A => 'a',
+        KeyCode::B => 'b',
+        KeyCode::C => 'c',
+        KeyCode::D => 'd',
+        KeyCode::E => 'e',
+        KeyCode::F => 'f',
+        KeyCode::G => 'g',
+        KeyCode::H => 'h',
+        KeyCode::I => 'i',
+        KeyCode::J => 'j',
+        KeyCode::K => 'k',
+        KeyCode::L => 'l',
+        KeyCode::M => 'm',
+        KeyCode::N => 'n',
+        KeyCode::O => 'o',
+        KeyCode::P => 'p',
+        KeyCode::Q => 'q',
+        KeyCode::R => 'r',
+        KeyCode::S => 's',
+        KeyCode::T => 't',
+        KeyCode::U => 'u',
+        KeyCode::V => 'v',
+        KeyCode::W => 'w',
+        KeyCode::X => 'x',
+        KeyCode::Y => 'y',
+        KeyCode::Z => 'z',
+        KeyCode::Key0 => '0',
+        KeyCode::Key1 => '1',
+        KeyCode::Key2 => '2',
+        KeyCode::Key3 => '3',
+        KeyCode::Key4 => '4',
+        KeyCode::Key5 => '5',
+        KeyCode::Key6 => '6',
+        KeyCode::Key7 => '7',
+        KeyCode::Key8 => '8',
+        KeyCode::Key9 => '9',
+        _ => return None,
+    };
+    Some(c)
 }
 
 impl clay::Renderer for MacroquadRenderer {
@@ -82,28 +496,28 @@ impl clay::Renderer for MacroquadRenderer {
     }
 
     fn render(&self, render_commands: &mut clay::RenderCommandIter<'_>) {
+        // Nested scroll containers clip to the intersection of their bounds
+        let mut scissors: Vec<Scissor> = Vec::new();
         for command in render_commands {
             match command.element() {
                 clay::RenderCommandElement::Rectangle(rectangle) => {
-                    // XXX handle rectangle.corner_radius https://github.com/not-fl3/macroquad/pull/448/files#diff-7287b746975aadc977a38a86ee3163de1d9030d2902e510ba9d955e5f9b6f3c1
-                    draw_rectangle(
-                        command.bounding_box.x,
-                        command.bounding_box.y,
-                        command.bounding_box.width,
-                        command.bounding_box.height,
+                    draw_rounded_rectangle(
+                        &command.bounding_box,
+                        &rectangle.corner_radius,
                         Color(rectangle.color).into(),
                     );
                 }
                 clay::RenderCommandElement::Text(text) => {
-                    let font = Some(get_font(text.font_id));
-                    let size = macroquad::prelude::measure_text(
-                        command.text.into(),
-                        font,
-                        text.font_size,
-                        1.0,
-                    );
+                    let fonts = self.fonts.borrow();
+                    let Some(font) = fonts.get(text.font_id as usize) else {
+                        continue;
+                    };
+                    let font = Some(font);
+                    let text_str: &str = command.text.into();
+                    let size =
+                        macroquad::prelude::measure_text(text_str, font, text.font_size, 1.0);
                     draw_text_ex(
-                        command.text.into(),
+                        text_str,
                         command.bounding_box.x,
                         command.bounding_box.y + size.offset_y, // draw_text_ex use baseline for y
                         TextParams {
@@ -115,112 +529,50 @@ impl clay::Renderer for MacroquadRenderer {
                     );
                 }
                 clay::RenderCommandElement::Border(border) => {
-                    // Left border
-                    if border.left.width > 0 {
-                        draw_rectangle(
-                            command.bounding_box.x,
-                            command.bounding_box.y + border.corner_radius.top_left,
-                            border.left.width as f32,
-                            command.bounding_box.height
-                                - border.corner_radius.top_left
-                                - border.corner_radius.bottom_left,
-                            Color(border.left.color).into(),
-                        );
-                    }
-                    // Right border
-                    if border.right.width > 0 {
-                        draw_rectangle(
-                            command.bounding_box.x + command.bounding_box.width
-                                - border.right.width as f32,
-                            command.bounding_box.y + border.corner_radius.top_right,
-                            border.right.width as f32,
-                            command.bounding_box.height
-                                - border.corner_radius.top_right
-                                - border.corner_radius.bottom_right,
-                            Color(border.right.color).into(),
-                        );
-                    }
-                    // Top border
-                    if border.top.width > 0 {
-                        draw_rectangle(
-                            command.bounding_box.x + border.corner_radius.top_left,
-                            command.bounding_box.y,
-                            command.bounding_box.width
-                                - border.corner_radius.top_left
-                                - border.corner_radius.top_right,
-                            border.top.width as f32,
-                            Color(border.top.color).into(),
-                        );
-                    }
-                    // Bottom border
-                    if border.bottom.width > 0 {
-                        draw_rectangle(
-                            command.bounding_box.x + border.corner_radius.bottom_left,
-                            command.bounding_box.y + command.bounding_box.height
-                                - border.bottom.width as f32,
-                            command.bounding_box.width
-                                - border.corner_radius.bottom_left
-                                - border.corner_radius.bottom_right,
-                            border.bottom.width as f32,
-                            Color(border.bottom.color).into(),
-                        );
-                    }
-                    if border.corner_radius.top_left > 0. {
-                        draw_ring(
-                            command.bounding_box.x + border.corner_radius.top_left,
-                            command.bounding_box.y + border.corner_radius.top_left,
-                            border.corner_radius.top_left - border.top.width as f32,
-                            border.corner_radius.top_left,
-                            180.,
-                            270.,
-                            10,
-                            border.top.color,
-                        );
-                    }
-                    if border.corner_radius.top_right > 0. {
-                        draw_ring(
-                            command.bounding_box.x + command.bounding_box.width
-                                - border.corner_radius.top_right,
-                            command.bounding_box.y + border.corner_radius.top_right,
-                            border.corner_radius.top_right - border.top.width as f32,
-                            border.corner_radius.top_right,
-                            270.,
-                            360.,
-                            10,
-                            border.top.color,
-                        );
-                    }
-                    if border.corner_radius.bottom_left > 0. {
-                        draw_ring(
-                            command.bounding_box.x + border.corner_radius.bottom_left,
-                            command.bounding_box.y + command.bounding_box.height
-                                - border.corner_radius.bottom_left,
-                            border.corner_radius.bottom_left - border.top.width as f32,
-                            border.corner_radius.bottom_left,
-                            90.,
-                            180.,
-                            10,
-                            border.bottom.color,
-                        );
+                    // between_children borders come from clay as Rectangle commands
+                    draw_border(&command.bounding_box, &border);
+                }
+                clay::RenderCommandElement::Image(image) => {
+                    if let Some(texture) = self.images.get(image.handle) {
+                        draw_image(&texture, &command.bounding_box, &image);
                     }
-                    if border.corner_radius.bottom_right > 0. {
-                        draw_ring(
-                            command.bounding_box.x + command.bounding_box.width
-                                - border.corner_radius.bottom_right,
-                            command.bounding_box.y + command.bounding_box.height
-                                - border.corner_radius.bottom_right,
-                            border.corner_radius.bottom_right - border.bottom.width as f32,
-                            border.corner_radius.bottom_right,
-                            0.1,
-                            90.,
-                            10,
-                            border.bottom.color,
-                        );
+                }
+                clay::RenderCommandElement::Custom(_) => {
+                    if let Some(custom_renderer) = self.custom_renderer.borrow_mut().as_mut() {
+                        custom_renderer(command);
                     }
                 }
-                _ => {}
+                clay::RenderCommandElement::ScissorStart => {
+                    let scissor = scissor_rect(
+                        &command.bounding_box,
+                        scissors.last().copied(),
+                        screen_dpi_scale(),
+                    );
+                    scissors.push(scissor);
+                    set_scissor(Some(scissor));
+                }
+                clay::RenderCommandElement::ScissorEnd => {
+                    scissors.pop();
+                    set_scissor(scissors.last().copied());
+                }
+                clay::RenderCommandElement::None => {}
             }
         }
+        if !scissors.is_empty() {
+            set_scissor(None);
+        }
+    }
+
+    // Key presses and typed text from macroquad's input queue since the last frame
+    fn key_events(&self) -> Vec<clay::KeyEvent> {
+        let subscriber = self.input_subscriber.get().unwrap_or_else(|| {
+            let subscriber = utils::register_input_subscriber();
+            self.input_subscriber.set(Some(subscriber));
+            subscriber
+        });
+        let mut key_events = KeyEvents(Vec::new());
+        utils::repeat_all_miniquad_input(&mut key_events, subscriber);
+        key_events.0
     }
 }
 
@@ -236,3 +588,136 @@ impl From<Color> for macroquad::color::Color {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::iter::zip;
+
+    fn bounding_box(x: f32, y: f32, width: f32, height: f32) -> clay::BoundingBox {
+        clay::BoundingBox {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(
+            actual.distance(expected) < 1e-3,
+            "{actual} isn't {expected}"
+        );
+    }
+
+    #[test]
+    fn corner_segments_are_clamped() {
+        assert_eq!(corner_segments(0.), 1);
+        assert_eq!(corner_segments(10.), 8);
+        assert_eq!(corner_segments(1000.), 64);
+    }
+
+    #[test]
+    fn rounded_rectangle_geometry() {
+        let bounds = bounding_box(0., 0., 100., 40.);
+        let outline = rounded_rectangle_outline(&bounds, &clay::CornerRadius::new(50.));
+        // The radius is clamped to 20, each corner is an arc from one side to the next
+        let points = corner_segments(20.) + 1;
+        assert_eq!(outline.len(), 4 * points);
+        assert_near(outline[0], vec2(0., 20.));
+        assert_near(outline[points - 1], vec2(20., 0.));
+        assert_near(outline[points], vec2(80., 0.));
+        assert_near(outline[4 * points - 1], vec2(0., 20.));
+        assert!(outline.iter().all(
+            |point| (-1e-3..=100.001).contains(&point.x) && (-1e-3..=40.001).contains(&point.y)
+        ));
+
+        let sharp = rounded_rectangle_outline(&bounds, &clay::CornerRadius::default());
+        assert_eq!(
+            sharp,
+            [vec2(0., 0.), vec2(100., 0.), vec2(100., 40.), vec2(0., 40.)]
+        );
+
+        let mesh = rounded_rectangle_mesh(&bounds, &clay::CornerRadius::new(5.), WHITE);
+        let count = mesh.vertices.len() - 1;
+        assert_eq!(mesh.vertices[0].position, vec3(50., 20., 0.));
+        assert_eq!(mesh.indices.len(), count * 3);
+        // The fan closes on the first outline point
+        assert_eq!(mesh.indices[mesh.indices.len() - 3..], [0, count as u16, 1]);
+    }
+
+    #[test]
+    fn scissor_rect_scaled_and_nested() {
+        let bounds = bounding_box(10.5, 10., 20., 20.2);
+        // Rounded outwards to whole framebuffer pixels
+        assert_eq!(scissor_rect(&bounds, None, 2.), (21, 20, 40, 41));
+        assert_eq!(scissor_rect(&bounds, None, 1.), (10, 10, 21, 21));
+        assert_eq!(
+            scissor_rect(&bounds, Some((0, 0, 30, 30)), 2.),
+            (21, 20, 9, 10)
+        );
+        assert_eq!(
+            scissor_rect(&bounds, Some((100, 100, 10, 10)), 2.),
+            (100, 100, 0, 0)
+        );
+    }
+
+    fn border(widths: [u32; 4], corner_radius: f32) -> clay::Border {
+        let style = |width, color| clay::BorderStyle { width, color };
+        let [left, right, top, bottom] = widths;
+        clay::Border {
+            left: style(left, clay::Color::rgb(255., 0., 0.)),
+            right: style(right, clay::Color::rgb(0., 255., 0.)),
+            top: style(top, clay::Color::rgb(0., 0., 255.)),
+            bottom: style(bottom, clay::Color::rgb(255., 255., 255.)),
+            corner_radius: clay::CornerRadius::new(corner_radius),
+            ..clay::default()
+        }
+    }
+
+    #[test]
+    fn sharp_border_ring() {
+        let bounds = bounding_box(0., 0., 100., 50.);
+        let mesh = border_mesh(&bounds, &border([2, 0, 4, 0], 0.));
+        // Two points per corner, only the strip between outer and inner is drawn
+        assert_eq!(mesh.vertices.len(), 4 * 2 * 4);
+        assert_eq!(mesh.indices.len(), 8 * 6);
+        let point = |i: usize| &mesh.vertices[i * 4..i * 4 + 4];
+
+        // The inner edge is inset by each side's width
+        let top_left = point(0);
+        assert_eq!(top_left[1].position, vec3(0., 0., 0.));
+        assert_eq!(top_left[2].position, vec3(2., 4., 0.));
+        assert_eq!(point(2)[2].position, vec3(100., 4., 0.));
+        assert_eq!(point(4)[2].position, vec3(100., 50., 0.));
+        assert_eq!(point(6)[2].position, vec3(2., 50., 0.));
+
+        // Each corner goes from the color of the side before it to the one after,
+        // sides without a border are transparent
+        assert_eq!(top_left[1].color, [255, 0, 0, 255]);
+        assert_eq!(point(1)[1].color, [0, 0, 255, 255]);
+        assert_eq!(point(3)[1].color[3], 0);
+    }
+
+    #[test]
+    fn rounded_border_ring() {
+        let bounds = bounding_box(0., 0., 100., 50.);
+        let mesh = border_mesh(&bounds, &border([2, 2, 2, 2], 10.));
+        let points = 4 * (corner_segments(10.) + 1);
+        assert_eq!(mesh.vertices.len(), points * 4);
+        // Both fringes are drawn as well
+        assert_eq!(mesh.indices.len(), points * 3 * 6);
+        assert!(mesh
+            .indices
+            .iter()
+            .all(|&i| (i as usize) < mesh.vertices.len()));
+
+        // Starts on the left side, the inner radius is reduced by the width
+        let expected = [vec2(-1., 10.), vec2(0., 10.), vec2(2., 10.), vec2(3., 10.)];
+        for (vertex, expected) in zip(&mesh.vertices[..4], expected) {
+            assert_near(vertex.position.truncate(), expected);
+        }
+        let alphas: Vec<_> = mesh.vertices[..4].iter().map(|v| v.color[3]).collect();
+        assert_eq!(alphas, [0, 255, 255, 0]);
+    }
+}
@@ -0,0 +1,73 @@
+use clay::prelude::*;
+
+fn main() {
+    let font = std::fs::read(
+        "clay/clay/examples/introducing-clay-video-demo/resources/Roboto-Regular.ttf",
+    )
+    .unwrap();
+    let renderer = clay_renderer_software::SoftwareRenderer::new(300, 300);
+    let font_id = renderer
+        .add_font(clay_renderer_software::Font::from_bytes(font, Default::default()).unwrap());
+
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.set_measure_text_callback(renderer.text_measurer());
+    arena.render(&renderer, |builder| {
+        builder.build(
+            |builder| {
+                clay::Layout {
+                    layout_direction: clay::LayoutDirection::TopToBottom,
+                    padding: clay::Padding { x: 16, y: 16 },
+                    child_gap: 16,
+                    ..clay::default()
+                }
+                .attach(builder);
+
+                clay::Rectangle {
+                    color: clay::Color::rgb(255., 0., 0.),
+                    corner_radius: clay::CornerRadius::new(16.),
+                }
+                .attach(builder);
+            },
+            |builder| {
+                child_rect(builder, clay::Color::rgb(0., 255., 0.), font_id);
+                child_rect(builder, clay::Color::rgb(0., 0., 255.), font_id);
+                child_rect(builder, clay::Color::rgb(255., 0., 255.), font_id);
+            },
+        )
+    });
+    renderer.save_png("screenshot.png").unwrap();
+}
+
+fn child_rect(builder: &clay::Builder, color: clay::Color, font_id: u16) {
+    builder.build(
+        |builder| {
+            clay::Layout {
+                padding: clay::Padding { x: 16, y: 16 },
+                sizing: clay::Sizing {
+                    height: clay::SizingAxis::fixed(80.),
+                    ..clay::default()
+                },
+                ..clay::default()
+            }
+            .attach(builder);
+
+            clay::Rectangle {
+                color,
+                corner_radius: clay::CornerRadius::new(8.),
+            }
+            .attach(builder);
+            clay::Border::all_radius(2, clay::Color::rgb(0., 0., 0.), 8.).attach(builder);
+        },
+        |builder| {
+            clay::Text {
+                font_id,
+                font_size: 18,
+                text_color: clay::Color::rgb(0., 0., 0.),
+                ..clay::default()
+            }
+            .with("Foobar".into())
+            .attach(builder);
+        },
+    );
+}
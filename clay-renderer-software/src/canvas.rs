@@ -0,0 +1,303 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+// An RGBA8 pixel buffer, also the image type added to a SoftwareRenderer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+// Pixel bounds drawing is limited to, x1/y1 exclusive
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Clip {
+    pub(crate) x0: i32,
+    pub(crate) y0: i32,
+    pub(crate) x1: i32,
+    pub(crate) y1: i32,
+}
+
+impl Clip {
+    pub(crate) fn from_bounding_box(bounding_box: &clay::BoundingBox) -> Self {
+        Self {
+            x0: bounding_box.x.floor() as i32,
+            y0: bounding_box.y.floor() as i32,
+            x1: (bounding_box.x + bounding_box.width).ceil() as i32,
+            y1: (bounding_box.y + bounding_box.height).ceil() as i32,
+        }
+    }
+
+    pub(crate) fn intersect(&self, other: &Clip) -> Self {
+        Self {
+            x0: self.x0.max(other.x0),
+            y0: self.y0.max(other.y0),
+            x1: self.x1.min(other.x1),
+            y1: self.y1.min(other.y1),
+        }
+    }
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    // Panics if pixels is not width * height * 4 bytes
+    pub fn from_rgba(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(pixels.len(), width as usize * height as usize * 4);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    pub fn clear(&mut self, color: clay::Color) {
+        let rgba = [color.r as u8, color.g as u8, color.b as u8, color.a as u8];
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), png::EncodingError> {
+        let file = File::create(path)?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)
+    }
+
+    pub(crate) fn bounds(&self) -> Clip {
+        Clip {
+            x0: 0,
+            y0: 0,
+            x1: self.width as i32,
+            y1: self.height as i32,
+        }
+    }
+
+    // Source-over blend of color scaled by coverage (0-1)
+    fn blend(&mut self, x: i32, y: i32, color: clay::Color, coverage: f32) {
+        let alpha = color.a / 255. * coverage;
+        if alpha <= 0. {
+            return;
+        }
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let pixel = &mut self.pixels[i..i + 4];
+        let dst_alpha = pixel[3] as f32 / 255.;
+        let out_alpha = alpha + dst_alpha * (1. - alpha);
+        for (channel, src) in pixel.iter_mut().zip([color.r, color.g, color.b]) {
+            let dst = *channel as f32;
+            *channel = ((src * alpha + dst * dst_alpha * (1. - alpha)) / out_alpha).round() as u8;
+        }
+        pixel[3] = (out_alpha * 255.).round() as u8;
+    }
+
+    pub(crate) fn fill_rounded_rect(
+        &mut self,
+        bounding_box: &clay::BoundingBox,
+        corner_radius: &clay::CornerRadius,
+        color: clay::Color,
+        clip: &Clip,
+    ) {
+        let clip = clip
+            .intersect(&Clip::from_bounding_box(bounding_box))
+            .intersect(&self.bounds());
+        let shape = RoundedRect::new(bounding_box, corner_radius);
+        for y in clip.y0..clip.y1 {
+            for x in clip.x0..clip.x1 {
+                let coverage = shape.coverage(x as f32 + 0.5, y as f32 + 0.5);
+                self.blend(x, y, color, coverage);
+            }
+        }
+    }
+
+    pub(crate) fn stroke_border(
+        &mut self,
+        bounding_box: &clay::BoundingBox,
+        border: &clay::Border,
+        clip: &Clip,
+    ) {
+        let clip = clip
+            .intersect(&Clip::from_bounding_box(bounding_box))
+            .intersect(&self.bounds());
+        let (left, right, top, bottom) = (
+            border.left.width as f32,
+            border.right.width as f32,
+            border.top.width as f32,
+            border.bottom.width as f32,
+        );
+        let radius = &border.corner_radius;
+        let outer = RoundedRect::new(bounding_box, radius);
+        let inner = RoundedRect::new(
+            &clay::BoundingBox {
+                x: bounding_box.x + left,
+                y: bounding_box.y + top,
+                width: bounding_box.width - left - right,
+                height: bounding_box.height - top - bottom,
+            },
+            &clay::CornerRadius {
+                top_left: (radius.top_left - left.max(top)).max(0.),
+                top_right: (radius.top_right - right.max(top)).max(0.),
+                bottom_left: (radius.bottom_left - left.max(bottom)).max(0.),
+                bottom_right: (radius.bottom_right - right.max(bottom)).max(0.),
+            },
+        );
+        for y in clip.y0..clip.y1 {
+            for x in clip.x0..clip.x1 {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let coverage = outer.coverage(px, py) * (1. - inner.coverage(px, py));
+                if coverage <= 0. {
+                    continue;
+                }
+                // Color of the side the pixel is relatively closest to
+                let side = [
+                    (px - bounding_box.x, &border.left),
+                    (bounding_box.x + bounding_box.width - px, &border.right),
+                    (py - bounding_box.y, &border.top),
+                    (bounding_box.y + bounding_box.height - py, &border.bottom),
+                ]
+                .into_iter()
+                .filter(|(_, style)| style.width > 0)
+                .min_by(|(a, a_style), (b, b_style)| {
+                    (a / a_style.width as f32).total_cmp(&(b / b_style.width as f32))
+                });
+                if let Some((_, style)) = side {
+                    self.blend(x, y, style.color, coverage);
+                }
+            }
+        }
+    }
+
+    // Nearest neighbour scaled copy of image into bounding_box
+    pub(crate) fn draw_image(
+        &mut self,
+        bounding_box: &clay::BoundingBox,
+        image: &Canvas,
+        clip: &Clip,
+    ) {
+        if image.width == 0 || image.height == 0 {
+            return;
+        }
+        let clip = clip
+            .intersect(&Clip::from_bounding_box(bounding_box))
+            .intersect(&self.bounds());
+        let scale_x = image.width as f32 / bounding_box.width;
+        let scale_y = image.height as f32 / bounding_box.height;
+        for y in clip.y0..clip.y1 {
+            let src_y = ((y as f32 + 0.5 - bounding_box.y) * scale_y) as i64;
+            if !(0..image.height as i64).contains(&src_y) {
+                continue;
+            }
+            for x in clip.x0..clip.x1 {
+                let src_x = ((x as f32 + 0.5 - bounding_box.x) * scale_x) as i64;
+                if !(0..image.width as i64).contains(&src_x) {
+                    continue;
+                }
+                let [r, g, b, a] = image.pixel(src_x as u32, src_y as u32);
+                let color = clay::Color::rgba(r as f32, g as f32, b as f32, a as f32);
+                self.blend(x, y, color, 1.);
+            }
+        }
+    }
+
+    // Blends an 8 bit coverage mask, as rasterized by fontdue, with its top left at x, y
+    pub(crate) fn draw_mask(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: usize,
+        mask: &[u8],
+        color: clay::Color,
+        clip: &Clip,
+    ) {
+        let clip = clip.intersect(&self.bounds());
+        for (row, line) in mask.chunks_exact(width.max(1)).enumerate() {
+            let py = y + row as i32;
+            if py < clip.y0 || py >= clip.y1 {
+                continue;
+            }
+            for (column, &coverage) in line.iter().enumerate() {
+                let px = x + column as i32;
+                if px < clip.x0 || px >= clip.x1 {
+                    continue;
+                }
+                self.blend(px, py, color, coverage as f32 / 255.);
+            }
+        }
+    }
+}
+
+// Signed distance based anti-aliasing
+struct RoundedRect {
+    center_x: f32,
+    center_y: f32,
+    half_width: f32,
+    half_height: f32,
+    radius: clay::CornerRadius,
+}
+
+impl RoundedRect {
+    fn new(bounding_box: &clay::BoundingBox, radius: &clay::CornerRadius) -> Self {
+        let half_width = bounding_box.width / 2.;
+        let half_height = bounding_box.height / 2.;
+        let max_radius = half_width.min(half_height).max(0.);
+        Self {
+            center_x: bounding_box.x + half_width,
+            center_y: bounding_box.y + half_height,
+            half_width,
+            half_height,
+            radius: clay::CornerRadius {
+                top_left: radius.top_left.clamp(0., max_radius),
+                top_right: radius.top_right.clamp(0., max_radius),
+                bottom_left: radius.bottom_left.clamp(0., max_radius),
+                bottom_right: radius.bottom_right.clamp(0., max_radius),
+            },
+        }
+    }
+
+    fn coverage(&self, x: f32, y: f32) -> f32 {
+        if self.half_width <= 0. || self.half_height <= 0. {
+            return 0.;
+        }
+        let dx = x - self.center_x;
+        let dy = y - self.center_y;
+        let radius = match (dx < 0., dy < 0.) {
+            (true, true) => self.radius.top_left,
+            (false, true) => self.radius.top_right,
+            (true, false) => self.radius.bottom_left,
+            (false, false) => self.radius.bottom_right,
+        };
+        let qx = dx.abs() - self.half_width + radius;
+        let qy = dy.abs() - self.half_height + radius;
+        let outside = qx.max(0.).hypot(qy.max(0.));
+        let distance = outside + qx.max(qy).min(0.) - radius;
+        (0.5 - distance).clamp(0., 1.)
+    }
+}
@@ -0,0 +1,458 @@
+use std::{
+    cell::{Ref, RefCell},
+    path::Path,
+    rc::Rc,
+};
+
+mod canvas;
+
+pub use crate::canvas::Canvas;
+use crate::canvas::Clip;
+pub use fontdue::Font;
+
+// Renders into an in-memory Canvas, no window or GPU required
+pub struct SoftwareRenderer {
+    canvas: RefCell<Canvas>,
+    clear_color: clay::Color,
+    // Shared with the text measurer registered on the arena
+    fonts: Rc<RefCell<Vec<Font>>>,
+    images: clay::Images<Canvas>,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            canvas: RefCell::new(Canvas::new(width, height)),
+            clear_color: clay::Color::rgba(0., 0., 0., 0.),
+            fonts: Rc::new(RefCell::new(Vec::new())),
+            images: clay::Images::new(),
+        }
+    }
+
+    pub fn set_clear_color(&mut self, color: clay::Color) {
+        self.clear_color = color;
+    }
+
+    pub fn add_font(&self, font: Font) -> u16 {
+        let mut fonts = self.fonts.borrow_mut();
+        fonts.push(font);
+        (fonts.len() - 1) as u16
+    }
+
+    // Register with clay::Arena::set_measure_text_callback
+    pub fn text_measurer(&self) -> impl clay::TextMeasurer + 'static {
+        let fonts = Rc::clone(&self.fonts);
+        move |text: &str, config: &clay::Text| {
+            let fonts = fonts.borrow();
+            // Text in a font that was never added takes no space and isn't drawn
+            let Some(font) = fonts.get(config.font_id as usize) else {
+                return clay::Dimensions::default();
+            };
+            let (width, height, _) = measure_text(font, text, config);
+            clay::Dimensions { width, height }
+        }
+    }
+
+    // The last rendered frame
+    pub fn canvas(&self) -> Ref<'_, Canvas> {
+        self.canvas.borrow()
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), png::EncodingError> {
+        self.canvas.borrow().save_png(path)
+    }
+
+    fn draw_text(
+        &self,
+        canvas: &mut Canvas,
+        bounding_box: &clay::BoundingBox,
+        text: &str,
+        config: &clay::Text,
+        clip: &Clip,
+    ) {
+        let fonts = self.fonts.borrow();
+        let Some(font) = fonts.get(config.font_id as usize) else {
+            return;
+        };
+        let font_size = config.font_size as f32;
+        let (_, _, baseline) = measure_text(font, text, config);
+        let baseline = bounding_box.y + baseline;
+        let mut x = bounding_box.x;
+        let mut previous = None;
+        for c in text.chars() {
+            if let Some(kern) = previous.and_then(|p| font.horizontal_kern(p, c, font_size)) {
+                x += kern;
+            }
+            let (metrics, mask) = font.rasterize(c, font_size);
+            canvas.draw_mask(
+                (x + metrics.xmin as f32).round() as i32,
+                (baseline - (metrics.height as i32 + metrics.ymin) as f32).round() as i32,
+                metrics.width,
+                &mask,
+                config.text_color,
+                clip,
+            );
+            x += metrics.advance_width + config.letter_spacing as f32;
+            previous = Some(c);
+        }
+    }
+}
+
+// Width, height and baseline offset from the top of the line
+fn measure_text(font: &Font, text: &str, config: &clay::Text) -> (f32, f32, f32) {
+    let font_size = config.font_size as f32;
+    let mut width = 0.;
+    let mut previous = None;
+    for c in text.chars() {
+        if let Some(kern) = previous.and_then(|p| font.horizontal_kern(p, c, font_size)) {
+            width += kern;
+        }
+        width += font.metrics(c, font_size).advance_width + config.letter_spacing as f32;
+        previous = Some(c);
+    }
+    let (ascent, descent) = font
+        .horizontal_line_metrics(font_size)
+        .map_or((font_size, 0.), |metrics| (metrics.ascent, metrics.descent));
+    let font_height = ascent - descent;
+    if config.line_height > 0 {
+        let line_height = config.line_height as f32;
+        (
+            width,
+            line_height,
+            (line_height - font_height) / 2. + ascent,
+        )
+    } else {
+        (width, font_height, ascent)
+    }
+}
+
+impl clay::ImageRegistry for SoftwareRenderer {
+    type Image = Canvas;
+
+    fn images(&self) -> &clay::Images<Canvas> {
+        &self.images
+    }
+
+    fn dimensions(image: &Canvas) -> Option<clay::Dimensions> {
+        Some(clay::Dimensions::new(
+            image.width() as f32,
+            image.height() as f32,
+        ))
+    }
+}
+
+impl clay::Renderer for SoftwareRenderer {
+    fn prepare_frame(&self) -> clay::Dimensions {
+        let mut canvas = self.canvas.borrow_mut();
+        canvas.clear(self.clear_color);
+        clay::Dimensions {
+            width: canvas.width() as f32,
+            height: canvas.height() as f32,
+        }
+    }
+
+    fn render(&self, render_commands: &mut clay::RenderCommandIter<'_>) {
+        let mut canvas = self.canvas.borrow_mut();
+        let mut clip_stack = vec![canvas.bounds()];
+        for command in render_commands {
+            let clip = *clip_stack.last().unwrap();
+            match command.element() {
+                clay::RenderCommandElement::Rectangle(rectangle) => {
+                    canvas.fill_rounded_rect(
+                        &command.bounding_box,
+                        &rectangle.corner_radius,
+                        rectangle.color,
+                        &clip,
+                    );
+                }
+                clay::RenderCommandElement::Border(border) => {
+                    canvas.stroke_border(&command.bounding_box, &border, &clip);
+                }
+                clay::RenderCommandElement::Text(text) => {
+                    self.draw_text(
+                        &mut canvas,
+                        &command.bounding_box,
+                        command.text.into(),
+                        &text,
+                        &clip,
+                    );
+                }
+                clay::RenderCommandElement::Image(image) => {
+                    if let Some(image) = self.images.get(image.handle) {
+                        canvas.draw_image(&command.bounding_box, &image, &clip);
+                    }
+                }
+                clay::RenderCommandElement::ScissorStart => {
+                    clip_stack
+                        .push(clip.intersect(&Clip::from_bounding_box(&command.bounding_box)));
+                }
+                clay::RenderCommandElement::ScissorEnd if clip_stack.len() > 1 => {
+                    clip_stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clay::prelude::*;
+    use clay::ImageRegistry;
+    use std::sync::Mutex;
+
+    // Clay state is global, tests must not lay out concurrently
+    static CLAY: Mutex<()> = Mutex::new(());
+
+    // The font used by the examples, from the clay submodule
+    const FONT: &[u8] = include_bytes!(
+        "../../clay/clay/examples/introducing-clay-video-demo/resources/Roboto-Regular.ttf"
+    );
+
+    fn sized(builder: &clay::Builder, width: f32, height: f32) {
+        clay::Layout::new()
+            .sizing(clay::Sizing::fixed(width, height))
+            .attach(builder);
+    }
+
+    #[test]
+    fn render_rounded_rect_and_scissor() {
+        let _clay = CLAY.lock().unwrap();
+        let renderer = SoftwareRenderer::new(100, 100);
+        let mut arena =
+            clay::Arena::with_capacity(clay::Dimensions::new(100., 100.), clay::default());
+        arena.render(&renderer, |builder| {
+            builder.build(
+                |builder| {
+                    clay::Layout {
+                        sizing: clay::Sizing {
+                            width: clay::SizingAxis::fixed(50.),
+                            height: clay::SizingAxis::fixed(50.),
+                        },
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                    clay::Scroll {
+                        horizontal: true,
+                        vertical: true,
+                    }
+                    .attach(builder);
+                    clay::Rectangle {
+                        color: clay::Color::rgb(255., 0., 0.),
+                        corner_radius: clay::CornerRadius::new(10.),
+                    }
+                    .attach(builder);
+                },
+                |builder| {
+                    builder.build(
+                        |builder| {
+                            clay::Layout {
+                                sizing: clay::Sizing {
+                                    width: clay::SizingAxis::fixed(80.),
+                                    height: clay::SizingAxis::fixed(20.),
+                                },
+                                ..clay::default()
+                            }
+                            .attach(builder);
+                            clay::Rectangle {
+                                color: clay::Color::rgb(0., 0., 255.),
+                                ..clay::default()
+                            }
+                            .attach(builder);
+                        },
+                        clay::no_children,
+                    )
+                },
+            )
+        });
+
+        let canvas = renderer.canvas();
+        assert_eq!(canvas.pixel(25, 40), [255, 0, 0, 255]);
+        // Rounded corner left transparent
+        assert_eq!(canvas.pixel(0, 49), [0, 0, 0, 0]);
+        assert_eq!(canvas.pixel(25, 10), [0, 0, 255, 255]);
+        // Child clipped to the scroll container
+        assert_eq!(canvas.pixel(60, 10), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn text_in_unknown_font_is_skipped() {
+        let _clay = CLAY.lock().unwrap();
+        let renderer = SoftwareRenderer::new(100, 100);
+        let mut arena =
+            clay::Arena::with_capacity(clay::Dimensions::new(100., 100.), clay::default());
+        arena.set_measure_text_callback(renderer.text_measurer());
+        arena.render(&renderer, |builder| {
+            clay::Text::new()
+                .font(3)
+                .size(16)
+                .with("No font".into())
+                .attach(builder);
+            builder.build(
+                |builder| {
+                    clay::Layout::new()
+                        .sizing(clay::Sizing::fixed(10., 10.))
+                        .attach(builder);
+                    clay::Rectangle {
+                        color: clay::Color::rgb(255., 0., 0.),
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                },
+                clay::no_children,
+            );
+        });
+
+        // The text took no space
+        assert_eq!(renderer.canvas().pixel(5, 5), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn render_border() {
+        let _clay = CLAY.lock().unwrap();
+        let renderer = SoftwareRenderer::new(100, 100);
+        let mut arena =
+            clay::Arena::with_capacity(clay::Dimensions::new(100., 100.), clay::default());
+        arena.render(&renderer, |builder| {
+            builder.build(
+                |builder| {
+                    sized(builder, 40., 40.);
+                    clay::Border {
+                        top: clay::BorderStyle {
+                            width: 4,
+                            color: clay::Color::rgb(0., 255., 0.),
+                        },
+                        ..clay::Border::all(clay::BorderStyle {
+                            width: 2,
+                            color: clay::Color::rgb(255., 0., 0.),
+                        })
+                    }
+                    .attach(builder);
+                },
+                clay::no_children,
+            )
+        });
+
+        let canvas = renderer.canvas();
+        assert_eq!(canvas.pixel(20, 3), [0, 255, 0, 255]);
+        assert_eq!(canvas.pixel(20, 4), [0, 0, 0, 0]);
+        assert_eq!(canvas.pixel(1, 20), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(2, 20), [0, 0, 0, 0]);
+        assert_eq!(canvas.pixel(38, 20), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(20, 38), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(20, 20), [0, 0, 0, 0]);
+        assert_eq!(canvas.pixel(41, 20), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn render_text() {
+        let _clay = CLAY.lock().unwrap();
+        let renderer = SoftwareRenderer::new(100, 100);
+        let font_id = renderer.add_font(Font::from_bytes(FONT, Default::default()).unwrap());
+        let mut arena =
+            clay::Arena::with_capacity(clay::Dimensions::new(100., 100.), clay::default());
+        arena.set_measure_text_callback(renderer.text_measurer());
+        let commands = arena.layout(clay::Dimensions::new(100., 100.), |builder| {
+            clay::Text::new()
+                .font(font_id)
+                .size(24)
+                .color(clay::Color::rgb(255., 255., 255.))
+                .with("Hi".into())
+                .attach(builder);
+        });
+        let text = commands[0].bounding_box();
+        assert!(text.width > 0. && text.height > 0.);
+
+        arena.render(&renderer, |builder| {
+            clay::Text::new()
+                .font(font_id)
+                .size(24)
+                .color(clay::Color::rgb(255., 255., 255.))
+                .with("Hi".into())
+                .attach(builder);
+        });
+        let canvas = renderer.canvas();
+        let inside = |x: u32, y: u32| {
+            (x as f32) < text.x + text.width + 1. && (y as f32) < text.y + text.height + 1.
+        };
+        let mut drawn = 0;
+        for y in 0..100 {
+            for x in 0..100 {
+                let [r, g, b, a] = canvas.pixel(x, y);
+                if a == 0 {
+                    continue;
+                }
+                assert!(inside(x, y), "text drawn outside its bounds at {x}, {y}");
+                // Anti-aliased edges are blended from the text color
+                assert_eq!((r, g, b), (255, 255, 255));
+                drawn += 1;
+            }
+        }
+        assert!(drawn > 10);
+    }
+
+    #[test]
+    fn render_image() {
+        let _clay = CLAY.lock().unwrap();
+        let renderer = SoftwareRenderer::new(100, 100);
+        // Red and green on the top row, blue and white on the bottom
+        #[rustfmt::skip]
+        let image = renderer.add_image(Canvas::from_rgba(2, 2, vec![
+            255, 0, 0, 255, 0, 255, 0, 255,
+            0, 0, 255, 255, 255, 255, 255, 255,
+        ]));
+        let mut arena =
+            clay::Arena::with_capacity(clay::Dimensions::new(100., 100.), clay::default());
+        arena.render(&renderer, |builder| {
+            builder.build(
+                |builder| {
+                    sized(builder, 40., 40.);
+                    renderer.image(image).attach(builder);
+                },
+                clay::no_children,
+            )
+        });
+
+        let canvas = renderer.canvas();
+        assert_eq!(canvas.pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(canvas.pixel(39, 0), [0, 255, 0, 255]);
+        assert_eq!(canvas.pixel(0, 39), [0, 0, 255, 255]);
+        assert_eq!(canvas.pixel(39, 39), [255, 255, 255, 255]);
+        assert_eq!(canvas.pixel(40, 40), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn save_png_round_trips() {
+        let _clay = CLAY.lock().unwrap();
+        let renderer = SoftwareRenderer::new(20, 10);
+        let mut arena =
+            clay::Arena::with_capacity(clay::Dimensions::new(20., 10.), clay::default());
+        arena.render(&renderer, |builder| {
+            builder.build(
+                |builder| {
+                    sized(builder, 10., 10.);
+                    clay::Rectangle {
+                        color: clay::Color::rgba(0., 0., 255., 128.),
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                },
+                clay::no_children,
+            )
+        });
+
+        let path = std::env::temp_dir().join(format!("clay-software-{}.png", std::process::id()));
+        renderer.save_png(&path).unwrap();
+        let decoder = png::Decoder::new(std::fs::File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((info.width, info.height), (20, 10));
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        assert_eq!(&pixels[..info.buffer_size()], renderer.canvas().pixels());
+        assert_eq!(renderer.canvas().pixel(5, 5)[2], 255);
+    }
+}
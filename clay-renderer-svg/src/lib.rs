@@ -0,0 +1,430 @@
+use std::{
+    cell::RefCell,
+    fmt::{self, Write},
+    path::Path,
+};
+
+// Renders each frame as a standalone SVG document
+pub struct SvgRenderer {
+    dimensions: clay::Dimensions,
+    // Font families indexed by clay::Text::font_id
+    font_families: RefCell<Vec<String>>,
+    images: clay::Images<Image>,
+    document: RefCell<String>,
+}
+
+// An <image> href, added with clay::ImageRegistry::add_image
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub href: String,
+    // Reported as the clay::Image source_dimensions if known
+    pub dimensions: Option<clay::Dimensions>,
+}
+
+impl Image {
+    pub fn new(href: impl Into<String>) -> Self {
+        Self {
+            href: href.into(),
+            dimensions: None,
+        }
+    }
+
+    pub fn with_dimensions(href: impl Into<String>, width: f32, height: f32) -> Self {
+        Self {
+            href: href.into(),
+            dimensions: Some(clay::Dimensions::new(width, height)),
+        }
+    }
+}
+
+impl SvgRenderer {
+    pub fn new(dimensions: clay::Dimensions) -> Self {
+        Self {
+            dimensions,
+            font_families: RefCell::new(Vec::new()),
+            images: clay::Images::new(),
+            document: RefCell::new(String::new()),
+        }
+    }
+
+    pub fn add_font_family(&self, family: &str) -> u16 {
+        let mut font_families = self.font_families.borrow_mut();
+        font_families.push(family.to_owned());
+        (font_families.len() - 1) as u16
+    }
+
+    // The last rendered frame
+    pub fn document(&self) -> String {
+        self.document.borrow().clone()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.document.borrow().as_bytes())
+    }
+
+    fn write_document(
+        &self,
+        svg: &mut String,
+        render_commands: &mut clay::RenderCommandIter<'_>,
+    ) -> fmt::Result {
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+            self.dimensions.width, self.dimensions.height
+        )?;
+        let mut clip_count = 0;
+        let mut depth = 1;
+        for command in render_commands {
+            let bounding_box = &command.bounding_box;
+            let indent = "  ".repeat(depth);
+            match command.element() {
+                clay::RenderCommandElement::Rectangle(rectangle) => {
+                    write!(svg, "{indent}")?;
+                    write_rounded_rect(svg, bounding_box, &rectangle.corner_radius)?;
+                    writeln!(svg, " {}/>", Paint("fill", rectangle.color))?;
+                }
+                clay::RenderCommandElement::Border(border) => {
+                    write_border(svg, &indent, bounding_box, &border)?;
+                }
+                clay::RenderCommandElement::Text(text) => {
+                    let font_families = self.font_families.borrow();
+                    let family = font_families
+                        .get(text.font_id as usize)
+                        .map_or("sans-serif", String::as_str);
+                    write!(
+                        svg,
+                        r#"{indent}<text x="{}" y="{}" dominant-baseline="central" font-family="{}" font-size="{}" {}"#,
+                        bounding_box.x,
+                        bounding_box.y + bounding_box.height / 2.,
+                        Escape(family),
+                        text.font_size,
+                        Paint("fill", text.text_color),
+                    )?;
+                    if text.letter_spacing > 0 {
+                        write!(svg, r#" letter-spacing="{}""#, text.letter_spacing)?;
+                    }
+                    writeln!(svg, ">{}</text>", Escape(command.text.into()))?;
+                }
+                clay::RenderCommandElement::Image(image) => {
+                    if let Some(image) = self.images.get(image.handle) {
+                        writeln!(
+                            svg,
+                            r#"{indent}<image x="{}" y="{}" width="{}" height="{}" href="{}"/>"#,
+                            bounding_box.x,
+                            bounding_box.y,
+                            bounding_box.width,
+                            bounding_box.height,
+                            Escape(&image.href),
+                        )?;
+                    }
+                }
+                clay::RenderCommandElement::ScissorStart => {
+                    clip_count += 1;
+                    writeln!(svg, r#"{indent}<clipPath id="clip{clip_count}">"#)?;
+                    writeln!(
+                        svg,
+                        r#"{indent}  <rect x="{}" y="{}" width="{}" height="{}"/>"#,
+                        bounding_box.x, bounding_box.y, bounding_box.width, bounding_box.height,
+                    )?;
+                    writeln!(svg, "{indent}</clipPath>")?;
+                    writeln!(svg, r#"{indent}<g clip-path="url(#clip{clip_count})">"#)?;
+                    depth += 1;
+                }
+                clay::RenderCommandElement::ScissorEnd if depth > 1 => {
+                    depth -= 1;
+                    writeln!(svg, "{}</g>", "  ".repeat(depth))?;
+                }
+                _ => {}
+            }
+        }
+        for depth in (1..depth).rev() {
+            writeln!(svg, "{}</g>", "  ".repeat(depth))?;
+        }
+        writeln!(svg, "</svg>")
+    }
+}
+
+impl clay::ImageRegistry for SvgRenderer {
+    type Image = Image;
+
+    fn images(&self) -> &clay::Images<Image> {
+        &self.images
+    }
+
+    fn dimensions(image: &Image) -> Option<clay::Dimensions> {
+        image.dimensions
+    }
+}
+
+impl clay::Renderer for SvgRenderer {
+    fn prepare_frame(&self) -> clay::Dimensions {
+        self.dimensions
+    }
+
+    fn render(&self, render_commands: &mut clay::RenderCommandIter<'_>) {
+        let mut svg = String::new();
+        // Writing to a String can't fail
+        self.write_document(&mut svg, render_commands).unwrap();
+        *self.document.borrow_mut() = svg;
+    }
+}
+
+// Writes an unterminated <rect> or <path> element
+fn write_rounded_rect(
+    svg: &mut String,
+    bounding_box: &clay::BoundingBox,
+    radius: &clay::CornerRadius,
+) -> fmt::Result {
+    let clay::BoundingBox {
+        x,
+        y,
+        width,
+        height,
+    } = *bounding_box;
+    let max_radius = (width / 2.).min(height / 2.).max(0.);
+    let [top_left, top_right, bottom_right, bottom_left] = [
+        radius.top_left,
+        radius.top_right,
+        radius.bottom_right,
+        radius.bottom_left,
+    ]
+    .map(|radius| radius.clamp(0., max_radius));
+    if top_left == top_right && top_left == bottom_right && top_left == bottom_left {
+        write!(
+            svg,
+            r#"<rect x="{x}" y="{y}" width="{width}" height="{height}""#
+        )?;
+        if top_left > 0. {
+            write!(svg, r#" rx="{top_left}""#)?;
+        }
+        return Ok(());
+    }
+    write!(
+        svg,
+        r#"<path d="M{} {y}H{}A{top_right} {top_right} 0 0 1 {} {}V{}A{bottom_right} {bottom_right} 0 0 1 {} {}H{}A{bottom_left} {bottom_left} 0 0 1 {x} {}V{}A{top_left} {top_left} 0 0 1 {} {y}Z""#,
+        x + top_left,
+        x + width - top_right,
+        x + width,
+        y + top_right,
+        y + height - bottom_right,
+        x + width - bottom_right,
+        y + height,
+        x + bottom_left,
+        y + height - bottom_left,
+        y + top_left,
+        x + top_left,
+    )
+}
+
+// Each side is stroked along its centre line, the top and bottom sides include the corners
+fn write_border(
+    svg: &mut String,
+    indent: &str,
+    bounding_box: &clay::BoundingBox,
+    border: &clay::Border,
+) -> fmt::Result {
+    let clay::BoundingBox {
+        x,
+        y,
+        width,
+        height,
+    } = *bounding_box;
+    let radius = &border.corner_radius;
+    let (left, right, top, bottom) = (
+        border.left.width as f32 / 2.,
+        border.right.width as f32 / 2.,
+        border.top.width as f32 / 2.,
+        border.bottom.width as f32 / 2.,
+    );
+    // Elliptical so the arc meets sides of different widths
+    let arc = |radius: f32, inset_x: f32, inset_y: f32| {
+        format!(
+            "A{} {} 0 0 1",
+            (radius - inset_x).max(0.),
+            (radius - inset_y).max(0.)
+        )
+    };
+    let mut side = |style: &clay::BorderStyle, d: String| -> fmt::Result {
+        if style.width == 0 {
+            return Ok(());
+        }
+        writeln!(
+            svg,
+            r#"{indent}<path d="{d}" fill="none" {} stroke-width="{}"/>"#,
+            Paint("stroke", style.color),
+            style.width
+        )
+    };
+
+    let mut d = if radius.top_left > 0. {
+        format!(
+            "M{} {}{} {} {}",
+            x + left,
+            y + radius.top_left,
+            arc(radius.top_left, left, top),
+            x + radius.top_left,
+            y + top
+        )
+    } else {
+        format!("M{x} {}", y + top)
+    };
+    if radius.top_right > 0. {
+        d += &format!(
+            "H{}{} {} {}",
+            x + width - radius.top_right,
+            arc(radius.top_right, right, top),
+            x + width - right,
+            y + radius.top_right
+        );
+    } else {
+        d += &format!("H{}", x + width);
+    }
+    side(&border.top, d)?;
+
+    let mut d = if radius.bottom_right > 0. {
+        format!(
+            "M{} {}{} {} {}",
+            x + width - right,
+            y + height - radius.bottom_right,
+            arc(radius.bottom_right, right, bottom),
+            x + width - radius.bottom_right,
+            y + height - bottom
+        )
+    } else {
+        format!("M{} {}", x + width, y + height - bottom)
+    };
+    if radius.bottom_left > 0. {
+        d += &format!(
+            "H{}{} {} {}",
+            x + radius.bottom_left,
+            arc(radius.bottom_left, left, bottom),
+            x + left,
+            y + height - radius.bottom_left
+        );
+    } else {
+        d += &format!("H{x}");
+    }
+    side(&border.bottom, d)?;
+
+    side(
+        &border.left,
+        format!(
+            "M{} {}V{}",
+            x + left,
+            y + radius.top_left.max(top * 2.),
+            y + height - radius.bottom_left.max(bottom * 2.),
+        ),
+    )?;
+    side(
+        &border.right,
+        format!(
+            "M{} {}V{}",
+            x + width - right,
+            y + radius.top_right.max(top * 2.),
+            y + height - radius.bottom_right.max(bottom * 2.),
+        ),
+    )
+}
+
+// fill="rgb(..)" or stroke="rgb(..)", with an opacity if not opaque
+struct Paint(&'static str, clay::Color);
+
+impl fmt::Display for Paint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Paint(attribute, color) = self;
+        write!(
+            f,
+            r#"{attribute}="rgb({},{},{})""#,
+            color.r.round(),
+            color.g.round(),
+            color.b.round()
+        )?;
+        if color.a < 255. {
+            write!(f, r#" {attribute}-opacity="{}""#, color.a / 255.)?;
+        }
+        Ok(())
+    }
+}
+
+struct Escape<'a>(&'a str);
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => f.write_str("&amp;")?,
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '"' => f.write_str("&quot;")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clay::prelude::*;
+
+    #[test]
+    fn render_svg_document() {
+        let dimensions = clay::Dimensions::new(100., 100.);
+        let renderer = SvgRenderer::new(dimensions);
+        let font_id = renderer.add_font_family("Roboto");
+        let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+        arena.set_measure_text_callback(|text: &str, config: &clay::Text| clay::Dimensions {
+            width: (text.len() * config.font_size as usize) as f32,
+            height: config.font_size as f32,
+        });
+        arena.render(&renderer, |builder| {
+            builder.build(
+                |builder| {
+                    clay::Layout {
+                        sizing: clay::Sizing {
+                            width: clay::SizingAxis::fixed(50.),
+                            height: clay::SizingAxis::fixed(50.),
+                        },
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                    clay::Scroll {
+                        horizontal: false,
+                        vertical: true,
+                    }
+                    .attach(builder);
+                    clay::Rectangle {
+                        color: clay::Color::rgb(255., 0., 0.),
+                        corner_radius: clay::CornerRadius::new(10.),
+                    }
+                    .attach(builder);
+                },
+                |builder| {
+                    clay::Text {
+                        font_id,
+                        font_size: 10,
+                        text_color: clay::Color::rgba(0., 0., 0., 127.5),
+                        ..clay::default()
+                    }
+                    .with("a<b".into())
+                    .attach(builder);
+                },
+            )
+        });
+
+        assert_eq!(
+            renderer.document(),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100" viewBox="0 0 100 100">
+  <clipPath id="clip1">
+    <rect x="0" y="0" width="50" height="50"/>
+  </clipPath>
+  <g clip-path="url(#clip1)">
+    <rect x="0" y="0" width="50" height="50" rx="10" fill="rgb(255,0,0)"/>
+    <text x="0" y="5" dominant-baseline="central" font-family="Roboto" font-size="10" fill="rgb(0,0,0)" fill-opacity="0.5">a&lt;b</text>
+  </g>
+</svg>
+"##
+        );
+    }
+}
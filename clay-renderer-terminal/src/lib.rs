@@ -0,0 +1,408 @@
+use std::{
+    cell::RefCell,
+    fmt::Write as _,
+    io::{self, Write},
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// One layout unit is one character cell
+pub struct TerminalRenderer {
+    columns: u16,
+    rows: u16,
+    cells: RefCell<Vec<Cell>>,
+}
+
+// Second cell of a double width glyph, the terminal draws the glyph over it
+const CONTINUATION: char = '\0';
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Cell {
+    character: char,
+    foreground: Option<Rgb>,
+    background: Option<Rgb>,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            character: ' ',
+            foreground: None,
+            background: None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Rgb(u8, u8, u8);
+
+impl Rgb {
+    // Alpha blends color over self, or over black if there is nothing underneath
+    fn blend(under: Option<Rgb>, color: clay::Color) -> Option<Rgb> {
+        let alpha = color.a / 255.;
+        if alpha <= 0. {
+            return under;
+        }
+        let Rgb(r, g, b) = under.unwrap_or(Rgb(0, 0, 0));
+        let mix = |under: u8, over: f32| (over * alpha + under as f32 * (1. - alpha)).round() as u8;
+        Some(Rgb(mix(r, color.r), mix(g, color.g), mix(b, color.b)))
+    }
+}
+
+// Cell bounds drawing is limited to, x1/y1 exclusive
+#[derive(Debug, Copy, Clone)]
+struct Clip {
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+}
+
+impl Clip {
+    fn from_bounding_box(bounding_box: &clay::BoundingBox) -> Self {
+        Self {
+            x0: bounding_box.x.round() as i32,
+            y0: bounding_box.y.round() as i32,
+            x1: (bounding_box.x + bounding_box.width).round() as i32,
+            y1: (bounding_box.y + bounding_box.height).round() as i32,
+        }
+    }
+
+    fn intersect(&self, other: &Clip) -> Self {
+        Self {
+            x0: self.x0.max(other.x0),
+            y0: self.y0.max(other.y0),
+            x1: self.x1.min(other.x1),
+            y1: self.y1.min(other.y1),
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x0 && x < self.x1 && y >= self.y0 && y < self.y1
+    }
+}
+
+// For Arena::set_measure_text_callback, text is as wide as the cells the terminal draws
+// it across and a line is one cell high
+pub fn measure_text(text: &str, _config: &clay::Text) -> clay::Dimensions {
+    clay::Dimensions {
+        width: text.width() as f32,
+        height: 1.,
+    }
+}
+
+impl TerminalRenderer {
+    pub fn new(columns: u16, rows: u16) -> Self {
+        Self {
+            columns,
+            rows,
+            cells: RefCell::new(vec![Cell::default(); columns as usize * rows as usize]),
+        }
+    }
+
+    // The last rendered frame without escapes, one line per row
+    pub fn plain_text(&self) -> String {
+        let cells = self.cells.borrow();
+        let mut text = String::new();
+        for row in cells.chunks(self.columns.max(1) as usize) {
+            text.extend(
+                row.iter()
+                    .map(|cell| cell.character)
+                    .filter(|&c| c != CONTINUATION),
+            );
+            text.push('\n');
+        }
+        text
+    }
+
+    // The last rendered frame with truecolor escapes, one line per row
+    pub fn ansi_text(&self) -> String {
+        let cells = self.cells.borrow();
+        let mut text = String::new();
+        for row in cells.chunks(self.columns.max(1) as usize) {
+            let (mut foreground, mut background) = (None, None);
+            for cell in row {
+                if cell.character == CONTINUATION {
+                    continue;
+                }
+                if cell.foreground != foreground || cell.background != background {
+                    text.push_str("\x1b[0m");
+                    if let Some(Rgb(r, g, b)) = cell.foreground {
+                        let _ = write!(text, "\x1b[38;2;{r};{g};{b}m");
+                    }
+                    if let Some(Rgb(r, g, b)) = cell.background {
+                        let _ = write!(text, "\x1b[48;2;{r};{g};{b}m");
+                    }
+                    (foreground, background) = (cell.foreground, cell.background);
+                }
+                text.push(cell.character);
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        text
+    }
+
+    // Redraws the last rendered frame from the top left of the terminal
+    pub fn write_to(&self, mut out: impl Write) -> io::Result<()> {
+        out.write_all(b"\x1b[H")?;
+        out.write_all(self.ansi_text().as_bytes())?;
+        out.flush()
+    }
+
+    fn cell(cells: &mut [Cell], columns: u16, x: i32, y: i32) -> &mut Cell {
+        &mut cells[y as usize * columns as usize + x as usize]
+    }
+
+    // Overwriting either half of a double width glyph blanks the other half
+    fn set_character(&self, cells: &mut [Cell], x: i32, y: i32, c: char) {
+        if Self::cell(cells, self.columns, x, y).character == CONTINUATION && x > 0 {
+            Self::cell(cells, self.columns, x - 1, y).character = ' ';
+        }
+        if x + 1 < self.columns as i32 {
+            let next = Self::cell(cells, self.columns, x + 1, y);
+            if next.character == CONTINUATION {
+                next.character = ' ';
+            }
+        }
+        Self::cell(cells, self.columns, x, y).character = c;
+    }
+
+    fn fill(&self, cells: &mut [Cell], clip: &Clip, color: clay::Color) {
+        for y in clip.y0..clip.y1 {
+            for x in clip.x0..clip.x1 {
+                self.set_character(cells, x, y, ' ');
+                let cell = Self::cell(cells, self.columns, x, y);
+                cell.background = Rgb::blend(cell.background, color);
+            }
+        }
+    }
+
+    fn put(&self, cells: &mut [Cell], clip: &Clip, x: i32, y: i32, c: char, color: clay::Color) {
+        if clip.contains(x, y) {
+            self.set_character(cells, x, y, c);
+            let cell = Self::cell(cells, self.columns, x, y);
+            cell.foreground = Rgb::blend(cell.background, color);
+        }
+    }
+
+    // Advances by the glyph's width in cells, double width glyphs are only drawn whole
+    fn put_glyph(
+        &self,
+        cells: &mut [Cell],
+        clip: &Clip,
+        x: i32,
+        y: i32,
+        c: char,
+        color: clay::Color,
+    ) -> i32 {
+        let width = c.width().unwrap_or(0) as i32;
+        match width {
+            1 => self.put(cells, clip, x, y, c, color),
+            2 if clip.contains(x, y) && clip.contains(x + 1, y) => {
+                self.put(cells, clip, x, y, c, color);
+                self.put(cells, clip, x + 1, y, CONTINUATION, color);
+            }
+            _ => {}
+        }
+        width
+    }
+
+    fn draw_border(
+        &self,
+        cells: &mut [Cell],
+        clip: &Clip,
+        bounding_box: &clay::BoundingBox,
+        border: &clay::Border,
+    ) {
+        let Clip { x0, y0, x1, y1 } = Clip::from_bounding_box(bounding_box);
+        let (x1, y1) = (x1 - 1, y1 - 1);
+        let radius = &border.corner_radius;
+        let (left, right, top, bottom) = (
+            border.left.width > 0,
+            border.right.width > 0,
+            border.top.width > 0,
+            border.bottom.width > 0,
+        );
+        if top {
+            for x in x0..=x1 {
+                self.put(cells, clip, x, y0, '─', border.top.color);
+            }
+        }
+        if bottom {
+            for x in x0..=x1 {
+                self.put(cells, clip, x, y1, '─', border.bottom.color);
+            }
+        }
+        if left {
+            for y in y0..=y1 {
+                self.put(cells, clip, x0, y, '│', border.left.color);
+            }
+        }
+        if right {
+            for y in y0..=y1 {
+                self.put(cells, clip, x1, y, '│', border.right.color);
+            }
+        }
+        let corner = |square, rounded, radius: f32| if radius > 0. { rounded } else { square };
+        if top && left {
+            let c = corner('┌', '╭', radius.top_left);
+            self.put(cells, clip, x0, y0, c, border.top.color);
+        }
+        if top && right {
+            let c = corner('┐', '╮', radius.top_right);
+            self.put(cells, clip, x1, y0, c, border.top.color);
+        }
+        if bottom && left {
+            let c = corner('└', '╰', radius.bottom_left);
+            self.put(cells, clip, x0, y1, c, border.bottom.color);
+        }
+        if bottom && right {
+            let c = corner('┘', '╯', radius.bottom_right);
+            self.put(cells, clip, x1, y1, c, border.bottom.color);
+        }
+    }
+}
+
+impl clay::Renderer for TerminalRenderer {
+    fn prepare_frame(&self) -> clay::Dimensions {
+        self.cells.borrow_mut().fill(Cell::default());
+        clay::Dimensions {
+            width: self.columns as f32,
+            height: self.rows as f32,
+        }
+    }
+
+    fn render(&self, render_commands: &mut clay::RenderCommandIter<'_>) {
+        let mut cells = self.cells.borrow_mut();
+        let mut clip_stack = vec![Clip {
+            x0: 0,
+            y0: 0,
+            x1: self.columns as i32,
+            y1: self.rows as i32,
+        }];
+        for command in render_commands {
+            let clip = *clip_stack.last().unwrap();
+            let bounds = clip.intersect(&Clip::from_bounding_box(&command.bounding_box));
+            match command.element() {
+                clay::RenderCommandElement::Rectangle(rectangle) => {
+                    self.fill(&mut cells, &bounds, rectangle.color);
+                }
+                clay::RenderCommandElement::Border(border) => {
+                    self.draw_border(&mut cells, &clip, &command.bounding_box, &border);
+                }
+                clay::RenderCommandElement::Text(text) => {
+                    let string: &str = command.text.into();
+                    let mut x = command.bounding_box.x.round() as i32;
+                    let y = command.bounding_box.y.round() as i32;
+                    for c in string.chars() {
+                        x += self.put_glyph(&mut cells, &bounds, x, y, c, text.text_color);
+                    }
+                }
+                clay::RenderCommandElement::ScissorStart => clip_stack.push(bounds),
+                clay::RenderCommandElement::ScissorEnd if clip_stack.len() > 1 => {
+                    clip_stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clay::prelude::*;
+    use std::sync::Mutex;
+
+    // Clay state is global, tests must not lay out concurrently
+    static CLAY: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn render_border_and_text() {
+        let _clay = CLAY.lock().unwrap();
+        let renderer = TerminalRenderer::new(12, 4);
+        let mut arena = clay::Arena::with_capacity(clay::Dimensions::new(12., 4.), clay::default());
+        arena.set_measure_text_callback(measure_text);
+        arena.render(&renderer, |builder| {
+            builder.build(
+                |builder| {
+                    clay::Layout {
+                        sizing: clay::Sizing {
+                            width: clay::SizingAxis::fixed(10.),
+                            height: clay::SizingAxis::fixed(3.),
+                        },
+                        padding: clay::Padding { x: 1, y: 1 },
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                    clay::Rectangle {
+                        color: clay::Color::rgb(0., 0., 255.),
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                    clay::Border::all_radius(1, clay::Color::rgb(255., 255., 255.), 1.)
+                        .attach(builder);
+                },
+                |builder| {
+                    clay::Text {
+                        text_color: clay::Color::rgb(255., 0., 0.),
+                        ..clay::default()
+                    }
+                    .with("Hello".into())
+                    .attach(builder);
+                },
+            )
+        });
+
+        assert_eq!(
+            renderer.plain_text(),
+            "╭────────╮  \n│Hello   │  \n╰────────╯  \n            \n"
+        );
+        assert!(renderer
+            .ansi_text()
+            .starts_with("\x1b[0m\x1b[38;2;255;255;255m\x1b[48;2;0;0;255m╭"));
+    }
+
+    #[test]
+    fn render_wide_text() {
+        let _clay = CLAY.lock().unwrap();
+        let renderer = TerminalRenderer::new(8, 2);
+        let mut arena = clay::Arena::with_capacity(clay::Dimensions::new(8., 2.), clay::default());
+        arena.set_measure_text_callback(measure_text);
+        arena.render(&renderer, |builder| {
+            builder.build(
+                |builder| {
+                    clay::Layout {
+                        layout_direction: clay::LayoutDirection::TopToBottom,
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                },
+                |builder| {
+                    clay::Text::new().with("日本語".into()).attach(builder);
+                    builder.build(
+                        |builder| {
+                            clay::Layout {
+                                sizing: clay::Sizing {
+                                    width: clay::SizingAxis::fixed(3.),
+                                    height: clay::SizingAxis::fixed(1.),
+                                },
+                                ..clay::default()
+                            }
+                            .attach(builder);
+                            clay::Scroll {
+                                horizontal: true,
+                                vertical: false,
+                            }
+                            .attach(builder);
+                        },
+                        |builder| clay::Text::new().with("日本".into()).attach(builder),
+                    );
+                },
+            )
+        });
+
+        // The second line is clipped through the middle of its second glyph
+        assert_eq!(renderer.plain_text(), "日本語  \n日      \n");
+        assert_eq!(measure_text("日本語", &clay::Text::new()).width, 6.);
+    }
+}
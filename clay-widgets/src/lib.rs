@@ -0,0 +1,1352 @@
+// Standard widgets built from the public Builder API, so any clay::Renderer can draw them
+use clay::prelude::*;
+use std::{cell::Cell, ops::RangeInclusive};
+
+// Popups are above modal dialogs so dropdowns and tooltips work inside them
+const MODAL_Z_INDEX: u16 = 100;
+const POPUP_Z_INDEX: u16 = 200;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Theme {
+    pub font_id: u16,
+    pub font_size: u16,
+    pub text_color: clay::Color,
+    pub background: clay::Color,
+    pub hovered: clay::Color,
+    pub pressed: clay::Color,
+    // Checked, selected and filled parts
+    pub accent: clay::Color,
+    // Ring around the focused widget
+    pub focus: clay::Color,
+    // Dropdown lists, tooltips and modal dialogs
+    pub popup_background: clay::Color,
+    // Drawn over the layout behind a modal dialog
+    pub overlay: clay::Color,
+    pub corner_radius: f32,
+    pub padding: clay::Padding,
+    pub gap: u16,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            font_id: 0,
+            font_size: 16,
+            text_color: clay::Color::rgb(255., 255., 255.),
+            background: clay::Color::rgb(90., 90., 90.),
+            hovered: clay::Color::rgb(120., 120., 120.),
+            pressed: clay::Color::rgb(70., 70., 70.),
+            accent: clay::Color::rgb(0., 120., 215.),
+            focus: clay::Color::rgb(100., 170., 255.),
+            popup_background: clay::Color::rgb(40., 40., 40.),
+            overlay: clay::Color::rgba(0., 0., 0., 150.),
+            corner_radius: 5.,
+            padding: clay::Padding::new(16, 8),
+            gap: 8,
+        }
+    }
+}
+
+impl Theme {
+    pub fn text(&self) -> clay::Text {
+        clay::Text::new()
+            .font(self.font_id)
+            .size(self.font_size)
+            .color(self.text_color)
+    }
+
+    // Background of something that can be pressed
+    pub fn surface(&self, interaction: &clay::Interaction) -> clay::Color {
+        if interaction.pressed {
+            self.pressed
+        } else if interaction.hovered {
+            self.hovered
+        } else {
+            self.background
+        }
+    }
+
+    fn rectangle(&self, color: clay::Color) -> clay::Rectangle {
+        clay::Rectangle {
+            color,
+            corner_radius: clay::CornerRadius::new(self.corner_radius),
+        }
+    }
+
+    fn label(&self, builder: &clay::Builder, text: &str) {
+        self.text().with(builder.store_text(text)).attach(builder);
+    }
+
+    fn padding(&self) -> clay::Layout {
+        clay::Layout::new().padding(self.padding.x, self.padding.y)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Response {
+    pub interaction: clay::Interaction,
+    // The value was changed by pointer or keyboard this frame
+    pub changed: bool,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct ModalResponse {
+    // Interaction with the backdrop, including the dialog on it
+    pub interaction: clay::Interaction,
+    // The backdrop was clicked or Escape pressed, the caller decides whether to close
+    pub dismissed: bool,
+}
+
+fn attach_id(builder: &clay::Builder, id: Option<clay::ElementId>) {
+    if let Some(id) = id {
+        id.attach(builder);
+    }
+}
+
+fn key_pressed(builder: &clay::Builder, pressed: clay::Key) -> bool {
+    builder
+        .key_events()
+        .iter()
+        .any(|event| matches!(event, clay::KeyEvent::Key { key, .. } if *key == pressed))
+}
+
+// Adds the open element to Tab traversal and rings it while focused,
+// true if it was activated with Enter or Space
+fn focusable(builder: &clay::Builder, theme: &Theme) -> bool {
+    builder.set_focusable();
+    if !builder.is_focused() {
+        return false;
+    }
+    clay::Border::outside_radius(2, theme.focus, theme.corner_radius).attach(builder);
+    key_pressed(builder, clay::Key::Enter) || key_pressed(builder, clay::Key::Space)
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Button<'a> {
+    label: &'a str,
+    id: Option<clay::ElementId<'a>>,
+}
+
+impl<'a> Button<'a> {
+    pub fn new(label: &'a str) -> Self {
+        Self { label, id: None }
+    }
+
+    pub fn id(self, id: clay::ElementId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    // clicked is also set when activated from the keyboard
+    pub fn build(self, builder: &clay::Builder, theme: &Theme) -> clay::Interaction {
+        let mut interaction = clay::Interaction::default();
+        builder.build(
+            |builder| {
+                attach_id(builder, self.id);
+                interaction = builder.interaction();
+                interaction.clicked |= focusable(builder, theme);
+                theme.padding().attach(builder);
+                theme.rectangle(theme.surface(&interaction)).attach(builder);
+            },
+            |builder| theme.label(builder, self.label),
+        );
+        interaction
+    }
+}
+
+pub struct Checkbox<'a> {
+    checked: &'a mut bool,
+    label: &'a str,
+    id: Option<clay::ElementId<'a>>,
+}
+
+impl<'a> Checkbox<'a> {
+    pub fn new(checked: &'a mut bool, label: &'a str) -> Self {
+        Self {
+            checked,
+            label,
+            id: None,
+        }
+    }
+
+    pub fn id(self, id: clay::ElementId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    pub fn build(self, builder: &clay::Builder, theme: &Theme) -> Response {
+        let checked = Cell::from_mut(self.checked);
+        let response = Cell::new(Response::default());
+        builder.build(
+            |builder| {
+                attach_id(builder, self.id);
+                let interaction = builder.interaction();
+                let changed = focusable(builder, theme) || interaction.clicked;
+                if changed {
+                    checked.set(!checked.get());
+                }
+                response.set(Response {
+                    interaction,
+                    changed,
+                });
+                clay::Layout::new()
+                    .gap(theme.gap)
+                    .align(clay::LayoutAlignmentX::Left, clay::LayoutAlignmentY::Center)
+                    .attach(builder);
+            },
+            |builder| {
+                let size = theme.font_size as f32;
+                builder.build(
+                    |builder| {
+                        clay::Layout::new()
+                            .sizing(clay::Sizing::fixed(size, size))
+                            .align(
+                                clay::LayoutAlignmentX::Center,
+                                clay::LayoutAlignmentY::Center,
+                            )
+                            .attach(builder);
+                        let color = if checked.get() {
+                            theme.accent
+                        } else {
+                            theme.surface(&response.get().interaction)
+                        };
+                        theme.rectangle(color).attach(builder);
+                    },
+                    |builder| {
+                        if checked.get() {
+                            builder.build(
+                                |builder| {
+                                    clay::Layout::new()
+                                        .sizing(clay::Sizing::fixed(size / 2., size / 2.))
+                                        .attach(builder);
+                                    theme.rectangle(theme.text_color).attach(builder);
+                                },
+                                clay::no_children,
+                            );
+                        }
+                    },
+                );
+                theme.label(builder, self.label);
+            },
+        );
+        response.get()
+    }
+}
+
+// An on/off switch
+pub struct Toggle<'a> {
+    on: &'a mut bool,
+    id: Option<clay::ElementId<'a>>,
+}
+
+impl<'a> Toggle<'a> {
+    pub fn new(on: &'a mut bool) -> Self {
+        Self { on, id: None }
+    }
+
+    pub fn id(self, id: clay::ElementId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    pub fn build(self, builder: &clay::Builder, theme: &Theme) -> Response {
+        let size = theme.font_size as f32;
+        let mut response = Response::default();
+        builder.build(
+            |builder| {
+                attach_id(builder, self.id);
+                let interaction = builder.interaction();
+                let changed = focusable(builder, theme) || interaction.clicked;
+                if changed {
+                    *self.on = !*self.on;
+                }
+                response = Response {
+                    interaction,
+                    changed,
+                };
+                let x = if *self.on {
+                    clay::LayoutAlignmentX::Right
+                } else {
+                    clay::LayoutAlignmentX::Left
+                };
+                clay::Layout::new()
+                    .sizing(clay::Sizing::fixed(size * 2., size))
+                    .padding(2, 2)
+                    .align(x, clay::LayoutAlignmentY::Center)
+                    .attach(builder);
+                clay::Rectangle {
+                    color: if *self.on {
+                        theme.accent
+                    } else {
+                        theme.surface(&interaction)
+                    },
+                    corner_radius: clay::CornerRadius::new(size / 2.),
+                }
+                .attach(builder);
+            },
+            |builder| {
+                let knob = size - 4.;
+                builder.build(
+                    |builder| {
+                        clay::Layout::new()
+                            .sizing(clay::Sizing::fixed(knob, knob))
+                            .attach(builder);
+                        clay::Rectangle {
+                            color: theme.text_color,
+                            corner_radius: clay::CornerRadius::new(knob / 2.),
+                        }
+                        .attach(builder);
+                    },
+                    clay::no_children,
+                );
+            },
+        );
+        response
+    }
+}
+
+// One of several options, each can be focused and selected on its own
+pub struct RadioGroup<'a> {
+    selected: &'a mut usize,
+    options: &'a [&'a str],
+    direction: clay::LayoutDirection,
+    id: Option<clay::ElementId<'a>>,
+}
+
+impl<'a> RadioGroup<'a> {
+    pub fn new(selected: &'a mut usize, options: &'a [&'a str]) -> Self {
+        Self {
+            selected,
+            options,
+            direction: clay::LayoutDirection::TopToBottom,
+            id: None,
+        }
+    }
+
+    pub fn direction(self, direction: clay::LayoutDirection) -> Self {
+        Self { direction, ..self }
+    }
+
+    pub fn id(self, id: clay::ElementId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    pub fn build(self, builder: &clay::Builder, theme: &Theme) -> Response {
+        let selected = Cell::from_mut(self.selected);
+        let mut interaction = clay::Interaction::default();
+        let changed = Cell::new(false);
+        let size = theme.font_size as f32;
+        builder.build(
+            |builder| {
+                attach_id(builder, self.id);
+                interaction = builder.interaction();
+                clay::Layout::new()
+                    .gap(theme.gap)
+                    .direction(self.direction)
+                    .attach(builder);
+            },
+            |builder| {
+                for (i, option) in self.options.iter().enumerate() {
+                    let option_interaction = Cell::new(clay::Interaction::default());
+                    builder.build(
+                        |builder| {
+                            let interaction = builder.interaction();
+                            if (focusable(builder, theme) || interaction.clicked)
+                                && selected.get() != i
+                            {
+                                selected.set(i);
+                                changed.set(true);
+                            }
+                            option_interaction.set(interaction);
+                            clay::Layout::new()
+                                .gap(theme.gap)
+                                .align(clay::LayoutAlignmentX::Left, clay::LayoutAlignmentY::Center)
+                                .attach(builder);
+                        },
+                        |builder| {
+                            builder.build(
+                                |builder| {
+                                    clay::Layout::new()
+                                        .sizing(clay::Sizing::fixed(size, size))
+                                        .align(
+                                            clay::LayoutAlignmentX::Center,
+                                            clay::LayoutAlignmentY::Center,
+                                        )
+                                        .attach(builder);
+                                    clay::Rectangle {
+                                        color: theme.surface(&option_interaction.get()),
+                                        corner_radius: clay::CornerRadius::new(size / 2.),
+                                    }
+                                    .attach(builder);
+                                },
+                                |builder| {
+                                    if selected.get() == i {
+                                        builder.build(
+                                            |builder| {
+                                                clay::Layout::new()
+                                                    .sizing(clay::Sizing::fixed(
+                                                        size / 2.,
+                                                        size / 2.,
+                                                    ))
+                                                    .attach(builder);
+                                                clay::Rectangle {
+                                                    color: theme.accent,
+                                                    corner_radius: clay::CornerRadius::new(
+                                                        size / 4.,
+                                                    ),
+                                                }
+                                                .attach(builder);
+                                            },
+                                            clay::no_children,
+                                        );
+                                    }
+                                },
+                            );
+                            theme.label(builder, option);
+                        },
+                    );
+                }
+            },
+        );
+        Response {
+            interaction,
+            changed: changed.get(),
+        }
+    }
+}
+
+pub struct Slider<'a> {
+    value: &'a mut f32,
+    range: RangeInclusive<f32>,
+    // Zero for a continuous value
+    step: f32,
+    width: f32,
+    id: Option<clay::ElementId<'a>>,
+}
+
+impl<'a> Slider<'a> {
+    pub fn new(value: &'a mut f32, range: RangeInclusive<f32>) -> Self {
+        Self {
+            value,
+            range,
+            step: 0.,
+            width: 200.,
+            id: None,
+        }
+    }
+
+    pub fn step(self, step: f32) -> Self {
+        Self { step, ..self }
+    }
+
+    pub fn width(self, width: f32) -> Self {
+        Self { width, ..self }
+    }
+
+    pub fn id(self, id: clay::ElementId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    // Dragging needs the bounds from the last layout, the arrow keys move by
+    // one step or a hundredth of the range
+    pub fn build(self, builder: &clay::Builder, theme: &Theme) -> Response {
+        let (min, max) = (*self.range.start(), *self.range.end());
+        let knob = theme.font_size as f32;
+        let travel = (self.width - knob).max(0.);
+        let value = Cell::from_mut(self.value);
+        let mut response = Response::default();
+        builder.build(
+            |builder| {
+                attach_id(builder, self.id);
+                let interaction = builder.interaction();
+                let mut new_value = value.get();
+                if let (true, Some(bounds)) = (interaction.pressed, builder.previous_bounds()) {
+                    let x = clay::Builder::pointer_data().position.x - bounds.x - knob / 2.;
+                    let fraction = if travel > 0. {
+                        (x / travel).clamp(0., 1.)
+                    } else {
+                        0.
+                    };
+                    new_value = min + fraction * (max - min);
+                }
+                focusable(builder, theme);
+                if builder.is_focused() {
+                    let step = if self.step > 0. {
+                        self.step
+                    } else {
+                        (max - min) / 100.
+                    };
+                    for event in builder.key_events() {
+                        if let clay::KeyEvent::Key { key, .. } = event {
+                            match key {
+                                clay::Key::Left | clay::Key::Down => new_value -= step,
+                                clay::Key::Right | clay::Key::Up => new_value += step,
+                                clay::Key::Home => new_value = min,
+                                clay::Key::End => new_value = max,
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                if self.step > 0. {
+                    new_value = min + ((new_value - min) / self.step).round() * self.step;
+                }
+                new_value = new_value.clamp(min, max);
+                response = Response {
+                    interaction,
+                    changed: new_value != value.get(),
+                };
+                value.set(new_value);
+                clay::Layout::new()
+                    .sizing(clay::Sizing::fixed(self.width, knob))
+                    .align(clay::LayoutAlignmentX::Left, clay::LayoutAlignmentY::Center)
+                    .attach(builder);
+            },
+            |builder| {
+                let fraction = if max > min {
+                    (value.get() - min) / (max - min)
+                } else {
+                    0.
+                };
+                let bar = |width: f32, color: clay::Color| {
+                    builder.build(
+                        |builder| {
+                            clay::Layout::new()
+                                .sizing(clay::Sizing::fixed(width, knob / 4.))
+                                .attach(builder);
+                            clay::Rectangle {
+                                color,
+                                corner_radius: clay::CornerRadius::new(knob / 8.),
+                            }
+                            .attach(builder);
+                        },
+                        clay::no_children,
+                    )
+                };
+                bar(travel * fraction, theme.accent);
+                builder.build(
+                    |builder| {
+                        clay::Layout::new()
+                            .sizing(clay::Sizing::fixed(knob, knob))
+                            .attach(builder);
+                        clay::Rectangle {
+                            color: theme.text_color,
+                            corner_radius: clay::CornerRadius::new(knob / 2.),
+                        }
+                        .attach(builder);
+                    },
+                    clay::no_children,
+                );
+                bar(travel * (1. - fraction), theme.background);
+            },
+        );
+        response
+    }
+}
+
+pub struct ProgressBar<'a> {
+    // Clamped to 0..=1
+    fraction: f32,
+    width: f32,
+    id: Option<clay::ElementId<'a>>,
+}
+
+impl<'a> ProgressBar<'a> {
+    pub fn new(fraction: f32) -> Self {
+        Self {
+            fraction,
+            width: 200.,
+            id: None,
+        }
+    }
+
+    pub fn width(self, width: f32) -> Self {
+        Self { width, ..self }
+    }
+
+    pub fn id(self, id: clay::ElementId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    pub fn build(self, builder: &clay::Builder, theme: &Theme) -> clay::Interaction {
+        let height = theme.font_size as f32 / 2.;
+        let radius = clay::CornerRadius::new(theme.corner_radius.min(height / 2.));
+        let mut interaction = clay::Interaction::default();
+        builder.build(
+            |builder| {
+                attach_id(builder, self.id);
+                interaction = builder.interaction();
+                clay::Layout::new()
+                    .sizing(clay::Sizing::fixed(self.width, height))
+                    .attach(builder);
+                clay::Rectangle {
+                    color: theme.background,
+                    corner_radius: radius,
+                }
+                .attach(builder);
+            },
+            |builder| {
+                builder.build(
+                    |builder| {
+                        clay::Layout::new()
+                            .sizing(clay::Sizing::fixed(
+                                self.width * self.fraction.clamp(0., 1.),
+                                height,
+                            ))
+                            .attach(builder);
+                        clay::Rectangle {
+                            color: theme.accent,
+                            corner_radius: radius,
+                        }
+                        .attach(builder);
+                    },
+                    clay::no_children,
+                );
+            },
+        );
+        interaction
+    }
+}
+
+// A row of tab labels, the caller lays out the selected page below it
+pub struct Tabs<'a> {
+    selected: &'a mut usize,
+    labels: &'a [&'a str],
+    id: Option<clay::ElementId<'a>>,
+}
+
+impl<'a> Tabs<'a> {
+    pub fn new(selected: &'a mut usize, labels: &'a [&'a str]) -> Self {
+        Self {
+            selected,
+            labels,
+            id: None,
+        }
+    }
+
+    pub fn id(self, id: clay::ElementId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    pub fn build(self, builder: &clay::Builder, theme: &Theme) -> Response {
+        let selected = Cell::from_mut(self.selected);
+        let mut interaction = clay::Interaction::default();
+        let changed = Cell::new(false);
+        builder.build(
+            |builder| {
+                attach_id(builder, self.id);
+                interaction = builder.interaction();
+            },
+            |builder| {
+                for (i, label) in self.labels.iter().enumerate() {
+                    builder.build(
+                        |builder| {
+                            let interaction = builder.interaction();
+                            if (focusable(builder, theme) || interaction.clicked)
+                                && selected.get() != i
+                            {
+                                selected.set(i);
+                                changed.set(true);
+                            }
+                            theme.padding().attach(builder);
+                            if interaction.hovered {
+                                theme.rectangle(theme.surface(&interaction)).attach(builder);
+                            }
+                            if selected.get() == i {
+                                let underline = clay::BorderStyle {
+                                    width: 2,
+                                    color: theme.accent,
+                                };
+                                clay::Border {
+                                    bottom: underline,
+                                    ..clay::default()
+                                }
+                                .attach(builder);
+                            }
+                        },
+                        |builder| theme.label(builder, label),
+                    );
+                }
+            },
+        );
+        Response {
+            interaction,
+            changed: changed.get(),
+        }
+    }
+}
+
+// A button showing the selected option that opens a list of the others,
+// the caller keeps whether it's open
+pub struct Dropdown<'a> {
+    selected: &'a mut usize,
+    open: &'a mut bool,
+    options: &'a [&'a str],
+    id: Option<clay::ElementId<'a>>,
+}
+
+impl<'a> Dropdown<'a> {
+    pub fn new(selected: &'a mut usize, open: &'a mut bool, options: &'a [&'a str]) -> Self {
+        Self {
+            selected,
+            open,
+            options,
+            id: None,
+        }
+    }
+
+    pub fn id(self, id: clay::ElementId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    // Up and Down change the selection while focused, Escape and pressing
+    // anywhere outside close the list
+    pub fn build(self, builder: &clay::Builder, theme: &Theme) -> Response {
+        let selected = Cell::from_mut(self.selected);
+        let open = Cell::from_mut(self.open);
+        let response = Cell::new(Response::default());
+        let list_hovered = Cell::new(false);
+        builder.build(
+            |builder| {
+                attach_id(builder, self.id);
+                let interaction = builder.interaction();
+                if focusable(builder, theme) || interaction.clicked {
+                    open.set(!open.get());
+                }
+                let mut changed = false;
+                if builder.is_focused() {
+                    for event in builder.key_events() {
+                        if let clay::KeyEvent::Key { key, .. } = event {
+                            let i = selected.get();
+                            match key {
+                                clay::Key::Up if i > 0 => selected.set(i - 1),
+                                clay::Key::Down if i + 1 < self.options.len() => {
+                                    selected.set(i + 1)
+                                }
+                                clay::Key::Escape => open.set(false),
+                                _ => continue,
+                            }
+                            changed |= selected.get() != i;
+                        }
+                    }
+                }
+                response.set(Response {
+                    interaction,
+                    changed,
+                });
+                theme.padding().attach(builder);
+                theme.rectangle(theme.surface(&interaction)).attach(builder);
+            },
+            |builder| {
+                let label = self.options.get(selected.get()).copied().unwrap_or("");
+                theme.label(builder, label);
+                if !open.get() {
+                    return;
+                }
+                // At least as wide as the button
+                let width = builder.previous_bounds().map_or(0., |bounds| bounds.width);
+                builder.build(
+                    |builder| {
+                        clay::Floating {
+                            z_index: POPUP_Z_INDEX,
+                            attachment: clay::FloatingAttachPoints {
+                                element: clay::FloatingAttachPointType::LeftTop,
+                                parent: clay::FloatingAttachPointType::LeftBottom,
+                            },
+                            ..clay::default()
+                        }
+                        .attach(builder);
+                        clay::Layout::new()
+                            .width(clay::SizingAxis::fit(width, f32::MAX))
+                            .direction(clay::LayoutDirection::TopToBottom)
+                            .attach(builder);
+                        theme.rectangle(theme.popup_background).attach(builder);
+                        list_hovered.set(clay::Builder::is_hovered());
+                    },
+                    |builder| {
+                        for (i, option) in self.options.iter().enumerate() {
+                            builder.build(
+                                |builder| {
+                                    let interaction = builder.interaction();
+                                    if interaction.clicked {
+                                        let mut changed = response.get();
+                                        changed.changed |= selected.get() != i;
+                                        response.set(changed);
+                                        selected.set(i);
+                                        open.set(false);
+                                    }
+                                    theme
+                                        .padding()
+                                        .width(clay::SizingAxis::grow_unbounded())
+                                        .attach(builder);
+                                    if interaction.hovered || selected.get() == i {
+                                        theme
+                                            .rectangle(theme.surface(&interaction))
+                                            .attach(builder);
+                                    }
+                                },
+                                |builder| theme.label(builder, option),
+                            );
+                        }
+                    },
+                );
+            },
+        );
+        let response = response.get();
+        let pressed = clay::Builder::pointer_data().state
+            == clay::PointerDataInteractionState::PressedThisFrame;
+        if pressed && !response.interaction.hovered && !list_hovered.get() {
+            open.set(false);
+        }
+        response
+    }
+}
+
+// Text shown over the element it's declared in while that element is hovered,
+// build it among the element's children
+pub struct Tooltip<'a> {
+    text: &'a str,
+}
+
+impl<'a> Tooltip<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self { text }
+    }
+
+    // True if shown
+    pub fn build(self, builder: &clay::Builder, theme: &Theme) -> bool {
+        // Outside of a child's closures the open element is the parent
+        if !clay::Builder::is_hovered() {
+            return false;
+        }
+        builder.build(
+            |builder| {
+                clay::Floating {
+                    offset: clay::Vector2 {
+                        x: 0.,
+                        y: -(theme.gap as f32),
+                    },
+                    z_index: POPUP_Z_INDEX,
+                    attachment: clay::FloatingAttachPoints {
+                        element: clay::FloatingAttachPointType::CenterBottom,
+                        parent: clay::FloatingAttachPointType::CenterTop,
+                    },
+                    pointer_capture_mode: clay::PointerCaptureMode::Passthrough,
+                    ..clay::default()
+                }
+                .attach(builder);
+                theme.padding().attach(builder);
+                theme.rectangle(theme.popup_background).attach(builder);
+            },
+            |builder| theme.label(builder, self.text),
+        );
+        true
+    }
+}
+
+// A dialog centered over the whole layout, which is dimmed and blocked from
+// the pointer, build it anywhere while it's open
+pub struct Modal<'a> {
+    title: Option<&'a str>,
+    id: Option<clay::ElementId<'a>>,
+}
+
+impl<'a> Modal<'a> {
+    pub fn new() -> Self {
+        Self {
+            title: None,
+            id: None,
+        }
+    }
+
+    pub fn title(self, title: &'a str) -> Self {
+        Self {
+            title: Some(title),
+            ..self
+        }
+    }
+
+    // Attached to the backdrop
+    pub fn id(self, id: clay::ElementId<'a>) -> Self {
+        Self {
+            id: Some(id),
+            ..self
+        }
+    }
+
+    pub fn build<F>(self, builder: &clay::Builder, theme: &Theme, content: F) -> ModalResponse
+    where
+        F: FnOnce(&clay::Builder),
+    {
+        let mut interaction = clay::Interaction::default();
+        let dialog_hovered = Cell::new(false);
+        builder.build(
+            |builder| {
+                attach_id(builder, self.id);
+                clay::Floating {
+                    z_index: MODAL_Z_INDEX,
+                    parent_id: clay::ElementId::ROOT.id(),
+                    ..clay::default()
+                }
+                .attach(builder);
+                clay::Layout::new()
+                    .sizing(clay::Sizing::expand())
+                    .align(
+                        clay::LayoutAlignmentX::Center,
+                        clay::LayoutAlignmentY::Center,
+                    )
+                    .attach(builder);
+                clay::Rectangle {
+                    color: theme.overlay,
+                    ..clay::default()
+                }
+                .attach(builder);
+                interaction = builder.interaction();
+            },
+            // Widgets under the backdrop can't be reached with the keyboard
+            |builder| {
+                builder.trap_focus(|builder| {
+                    builder.build(
+                        |builder| {
+                            theme
+                                .padding()
+                                .gap(theme.gap)
+                                .direction(clay::LayoutDirection::TopToBottom)
+                                .attach(builder);
+                            theme.rectangle(theme.popup_background).attach(builder);
+                            dialog_hovered.set(clay::Builder::is_hovered());
+                        },
+                        |builder| {
+                            if let Some(title) = self.title {
+                                theme.label(builder, title);
+                            }
+                            content(builder);
+                        },
+                    )
+                })
+            },
+        );
+        ModalResponse {
+            interaction,
+            dismissed: (interaction.clicked && !dialog_hovered.get())
+                || key_pressed(builder, clay::Key::Escape),
+        }
+    }
+}
+
+impl Default for Modal<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    // Clay state is global, tests must not lay out concurrently
+    static CLAY: Mutex<()> = Mutex::new(());
+
+    fn context() -> clay::Context<'static> {
+        let mut context =
+            clay::Context::with_capacity(clay::Dimensions::new(300., 300.), clay::default());
+        context
+            .arena()
+            .set_measure_text_callback(clay::testing::measure_text);
+        context
+    }
+
+    fn key(key: clay::Key) -> clay::KeyEvent {
+        clay::KeyEvent::Key {
+            key,
+            modifiers: clay::default(),
+        }
+    }
+
+    fn shift_tab() -> clay::KeyEvent {
+        clay::KeyEvent::Key {
+            key: clay::Key::Tab,
+            modifiers: clay::Modifiers {
+                shift: true,
+                ..clay::default()
+            },
+        }
+    }
+
+    fn texts(commands: &[clay::OwnedRenderCommand]) -> Vec<&str> {
+        commands
+            .iter()
+            .filter_map(|command| match command {
+                clay::OwnedRenderCommand::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // (x, y, width, height) of the rectangles in the given color
+    fn rectangles(
+        commands: &[clay::OwnedRenderCommand],
+        color: clay::Color,
+    ) -> Vec<(f32, f32, f32, f32)> {
+        commands
+            .iter()
+            .filter_map(|command| match command {
+                clay::OwnedRenderCommand::Rectangle {
+                    bounding_box: b,
+                    config,
+                    ..
+                } if (
+                    config.color.r,
+                    config.color.g,
+                    config.color.b,
+                    config.color.a,
+                ) == (color.r, color.g, color.b, color.a) =>
+                {
+                    Some((b.x, b.y, b.width, b.height))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn column(builder: &clay::Builder, children: impl FnOnce(&clay::Builder)) {
+        builder.build(
+            |builder| {
+                clay::Layout::new()
+                    .direction(clay::LayoutDirection::TopToBottom)
+                    .attach(builder)
+            },
+            children,
+        );
+    }
+
+    #[test]
+    fn button_and_checkbox() {
+        let _clay = CLAY.lock().unwrap();
+        let theme = Theme::default();
+        let mut context = context();
+        let mut checked = false;
+        let mut frame = |(x, y): (f32, f32), down: bool, key_events: &[clay::KeyEvent]| {
+            context.set_pointer_state(clay::Vector2 { x, y }, down);
+            context.set_key_events(key_events);
+            let mut responses = (clay::Interaction::default(), Response::default());
+            context.layout(clay::Dimensions::new(300., 300.), |builder| {
+                column(builder, |builder| {
+                    // 48x32 with the default theme
+                    responses.0 = Button::new("OK").build(builder, &theme);
+                    responses.1 = Checkbox::new(&mut checked, "Check").build(builder, &theme);
+                })
+            });
+            (responses.0, responses.1, checked)
+        };
+
+        // Hovering is found against the last layout
+        frame((10., 10.), false, &[]);
+        assert!(frame((10., 10.), false, &[]).0.hovered);
+        assert!(frame((10., 10.), true, &[]).0.pressed);
+        assert!(frame((10., 10.), false, &[]).0.clicked);
+
+        frame((5., 40.), true, &[]);
+        let (_, checkbox, checked) = frame((5., 40.), false, &[]);
+        assert!(checkbox.changed && checkbox.interaction.clicked && checked);
+        assert!(!frame((5., 40.), false, &[]).1.changed);
+
+        // Tab focuses the button, Enter and Space activate the focused widget
+        frame((200., 200.), false, &[key(clay::Key::Tab)]);
+        assert!(
+            frame((200., 200.), false, &[key(clay::Key::Enter)])
+                .0
+                .clicked
+        );
+        let (_, checkbox, checked) = frame(
+            (200., 200.),
+            false,
+            &[key(clay::Key::Tab), key(clay::Key::Space)],
+        );
+        assert!(checkbox.changed);
+        assert!(!checked);
+    }
+
+    #[test]
+    fn slider_and_dropdown() {
+        let _clay = CLAY.lock().unwrap();
+        let theme = Theme::default();
+        let mut context = context();
+        let mut value = 0.;
+        let mut selected = 0;
+        let mut open = false;
+        let mut frame = |(x, y): (f32, f32), down: bool, key_events: &[clay::KeyEvent]| {
+            context.set_pointer_state(clay::Vector2 { x, y }, down);
+            context.set_key_events(key_events);
+            let mut responses = (Response::default(), Response::default());
+            context.layout(clay::Dimensions::new(300., 300.), |builder| {
+                column(builder, |builder| {
+                    // 100x16 with the default theme
+                    responses.0 = Slider::new(&mut value, 0.0..=10.)
+                        .step(1.)
+                        .width(100.)
+                        .build(builder, &theme);
+                    // 40x32 button and a list of 32 high options below it
+                    responses.1 = Dropdown::new(&mut selected, &mut open, &["a", "b", "c"])
+                        .build(builder, &theme);
+                })
+            });
+            (responses, value, selected, open)
+        };
+
+        frame((50., 8.), false, &[]);
+        let ((slider, _), value, ..) = frame((50., 8.), true, &[]);
+        assert!(slider.changed);
+        assert_eq!(value, 5.);
+        let ((slider, _), value, ..) = frame((200., 8.), true, &[]);
+        assert!(slider.changed);
+        assert_eq!(value, 10.);
+        frame((200., 8.), false, &[]);
+        // Pressing focused the slider
+        let ((slider, _), value, ..) = frame((200., 8.), false, &[key(clay::Key::Home)]);
+        assert!(slider.changed);
+        assert_eq!(value, 0.);
+        let ((_, _), value, ..) = frame((200., 8.), false, &[key(clay::Key::Right)]);
+        assert_eq!(value, 1.);
+
+        frame((10., 30.), true, &[]);
+        assert!(frame((10., 30.), false, &[]).3);
+        frame((10., 128.), true, &[]);
+        let ((_, dropdown), _, selected, open) = frame((10., 128.), false, &[]);
+        assert!(dropdown.changed);
+        assert_eq!((selected, open), (2, false));
+
+        // Pressing outside closes the list without changing the selection
+        frame((10., 30.), true, &[]);
+        assert!(frame((10., 30.), false, &[]).3);
+        assert!(!frame((200., 200.), true, &[]).3);
+        assert_eq!(frame((200., 200.), false, &[]).2, 2);
+    }
+
+    #[test]
+    fn toggle_and_radio_group() {
+        let _clay = CLAY.lock().unwrap();
+        let theme = Theme::default();
+        let mut context = context();
+        let mut on = false;
+        let mut selected = 0;
+        let mut frame = |(x, y): (f32, f32), down: bool, key_events: &[clay::KeyEvent]| {
+            context.set_pointer_state(clay::Vector2 { x, y }, down);
+            context.set_key_events(key_events);
+            let mut responses = (Response::default(), Response::default());
+            context.layout(clay::Dimensions::new(300., 300.), |builder| {
+                column(builder, |builder| {
+                    // 32x16 with the default theme
+                    responses.0 = Toggle::new(&mut on).build(builder, &theme);
+                    // Options 16 high and 8 apart, starting at y 16
+                    responses.1 =
+                        RadioGroup::new(&mut selected, &["x", "y", "z"]).build(builder, &theme);
+                })
+            });
+            (responses, on, selected)
+        };
+
+        frame((5., 8.), false, &[]);
+        frame((5., 8.), true, &[]);
+        let ((toggle, radio), on, selected) = frame((5., 8.), false, &[]);
+        assert!(toggle.changed && on);
+        assert!(!radio.changed);
+        assert_eq!(selected, 0);
+
+        // Pressing focused the toggle, Space flips it back
+        let ((toggle, _), on, _) = frame((5., 8.), false, &[key(clay::Key::Space)]);
+        assert!(toggle.changed && !on);
+
+        frame((5., 70.), true, &[]);
+        let ((toggle, radio), _, selected) = frame((5., 70.), false, &[]);
+        assert!(!toggle.changed && radio.changed);
+        assert_eq!(selected, 2);
+        // Clicking the selected option again isn't a change
+        frame((5., 70.), true, &[]);
+        assert!(!frame((5., 70.), false, &[]).0 .1.changed);
+
+        // Each option is focusable and selected with Enter or Space
+        let ((_, radio), _, selected) =
+            frame((200., 200.), false, &[shift_tab(), key(clay::Key::Enter)]);
+        assert!(radio.changed);
+        assert_eq!(selected, 1);
+    }
+
+    #[test]
+    fn tabs_and_progress_bar() {
+        let _clay = CLAY.lock().unwrap();
+        let theme = Theme::default();
+        let mut context = context();
+        let mut selected = 0;
+        let mut frame = |(x, y): (f32, f32), down: bool, key_events: &[clay::KeyEvent]| {
+            context.set_pointer_state(clay::Vector2 { x, y }, down);
+            context.set_key_events(key_events);
+            let mut tabs = Response::default();
+            let commands = context.layout(clay::Dimensions::new(300., 300.), |builder| {
+                column(builder, |builder| {
+                    // 56x32 tabs side by side
+                    tabs = Tabs::new(&mut selected, &["One", "Two"]).build(builder, &theme);
+                    ProgressBar::new(0.25).width(200.).build(builder, &theme);
+                    ProgressBar::new(1.5).width(200.).build(builder, &theme);
+                })
+            });
+            (tabs, selected, commands)
+        };
+
+        let (tabs, selected, commands) = frame((70., 10.), false, &[]);
+        assert!(!tabs.changed);
+        assert_eq!(selected, 0);
+        // The fill is clamped to the bar
+        assert_eq!(
+            rectangles(&commands, theme.accent),
+            [(0., 32., 50., 8.), (0., 40., 200., 8.)]
+        );
+        assert_eq!(
+            rectangles(&commands, theme.background),
+            [(0., 32., 200., 8.), (0., 40., 200., 8.)]
+        );
+
+        frame((70., 10.), true, &[]);
+        let (tabs, selected, _) = frame((70., 10.), false, &[]);
+        assert!(tabs.changed);
+        assert_eq!(selected, 1);
+        // Tabs before the clicked one were declared before it changed
+        let (_, _, commands) = frame((70., 10.), false, &[]);
+        let underlines: Vec<_> = commands
+            .iter()
+            .filter_map(|command| match command {
+                clay::OwnedRenderCommand::Border {
+                    bounding_box: b, ..
+                } => Some((b.x, b.y)),
+                _ => None,
+            })
+            .collect();
+        // The selected tab is underlined, the focused one also has a ring
+        assert!(underlines.contains(&(56., 0.)));
+        assert!(!underlines.contains(&(0., 0.)));
+
+        let (tabs, selected, _) = frame((200., 200.), false, &[shift_tab(), key(clay::Key::Space)]);
+        assert!(tabs.changed);
+        assert_eq!(selected, 0);
+    }
+
+    #[test]
+    fn tooltip_shown_on_hover() {
+        let _clay = CLAY.lock().unwrap();
+        let theme = Theme::default();
+        let mut context = context();
+        let mut frame = |x: f32, y: f32| {
+            context.set_pointer_state(clay::Vector2 { x, y }, false);
+            let mut shown = false;
+            let commands = context.layout(clay::Dimensions::new(300., 300.), |builder| {
+                builder.build(
+                    |builder| {
+                        clay::Layout::new().padding(100, 100).attach(builder);
+                    },
+                    |builder| {
+                        builder.build(
+                            |builder| {
+                                clay::Layout::new()
+                                    .sizing(clay::Sizing::fixed(50., 20.))
+                                    .attach(builder);
+                            },
+                            |builder| shown = Tooltip::new("Tip").build(builder, &theme),
+                        )
+                    },
+                )
+            });
+            let texts = texts(&commands)
+                .iter()
+                .map(|text| text.to_string())
+                .collect();
+            (shown, texts)
+        };
+
+        assert_eq!(frame(110., 110.), (false, vec![]));
+        assert_eq!(frame(110., 110.), (true, vec!["Tip".to_string()]));
+        assert_eq!(frame(10., 10.), (false, vec![]));
+    }
+
+    #[test]
+    fn modal_dismissed_by_backdrop_or_escape() {
+        let _clay = CLAY.lock().unwrap();
+        let theme = Theme::default();
+        let mut context = context();
+        let mut frame = |(x, y): (f32, f32), down: bool, key_events: &[clay::KeyEvent]| {
+            context.set_pointer_state(clay::Vector2 { x, y }, down);
+            context.set_key_events(key_events);
+            let mut responses = (
+                clay::Interaction::default(),
+                ModalResponse::default(),
+                clay::Interaction::default(),
+            );
+            let commands = context.layout(clay::Dimensions::new(300., 300.), |builder| {
+                responses.0 = Button::new("Under").build(builder, &theme);
+                // An 80x72 dialog centered at (110, 114), OK is at (126, 146)
+                responses.1 = Modal::new()
+                    .title("Title")
+                    .build(builder, &theme, |builder| {
+                        responses.2 = Button::new("OK").build(builder, &theme);
+                    });
+            });
+            (responses, commands)
+        };
+
+        let (_, commands) = frame((10., 10.), false, &[]);
+        assert_eq!(texts(&commands), ["Under", "Title", "OK"]);
+        assert_eq!(rectangles(&commands, theme.overlay), [(0., 0., 300., 300.)]);
+        assert_eq!(
+            rectangles(&commands, theme.popup_background),
+            [(110., 114., 80., 72.)]
+        );
+
+        // The backdrop takes the pointer from what's beneath it
+        let ((under, modal, _), _) = frame((10., 10.), false, &[]);
+        assert!(!under.hovered && modal.interaction.hovered);
+
+        frame((150., 160.), true, &[]);
+        let ((_, modal, ok), _) = frame((150., 160.), false, &[]);
+        assert!(ok.clicked && modal.interaction.clicked);
+        assert!(!modal.dismissed);
+
+        frame((10., 10.), true, &[]);
+        let ((under, modal, _), _) = frame((10., 10.), false, &[]);
+        assert!(modal.dismissed && !under.clicked);
+
+        let ((_, modal, _), _) = frame((150., 160.), false, &[key(clay::Key::Escape)]);
+        assert!(modal.dismissed);
+
+        // Tab stays in the dialog, so Enter activates OK and never Under
+        let tab = [
+            key(clay::Key::Tab),
+            key(clay::Key::Tab),
+            key(clay::Key::Enter),
+        ];
+        let ((under, _, ok), _) = frame((10., 10.), false, &tab);
+        assert!(ok.clicked && !under.clicked);
+        let ((under, _, ok), _) = frame((10., 10.), false, &[shift_tab(), key(clay::Key::Space)]);
+        assert!(ok.clicked && !under.clicked);
+    }
+}
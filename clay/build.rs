@@ -1,6 +1,8 @@
 extern crate cc;
 
 fn main() {
+    println!("cargo:rerun-if-changed=clay.c");
+    println!("cargo:rerun-if-changed=clay/clay.h");
     cc::Build::new()
         .file("clay.c")
         .std("c99")
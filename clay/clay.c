@@ -1,2 +1,173 @@
 #define CLAY_IMPLEMENTATION
 #include "clay/clay.h"
+#include <stdlib.h>
+#include <string.h>
+
+// Clay keeps all of its state in globals. These save and restore that state
+// so several arenas can be used as independent contexts in one process.
+#define CLAY_RS_CONTEXT_STATE(X) \
+    X(Clay__warningsEnabled) \
+    X(Clay__maxElementCount) \
+    X(Clay__maxMeasureTextCacheWordCount) \
+    X(Clay__errorHandler) \
+    X(Clay__booleanWarnings) \
+    X(Clay__pointerInfo) \
+    X(Clay__layoutDimensions) \
+    X(Clay__dynamicElementIndexBaseHash) \
+    X(Clay__dynamicElementIndex) \
+    X(Clay__debugModeEnabled) \
+    X(Clay__disableCulling) \
+    X(Clay__externalScrollHandlingEnabled) \
+    X(Clay__debugSelectedElementId) \
+    X(Clay__debugViewWidth) \
+    X(Clay__debugViewHighlightColor) \
+    X(Clay__generation) \
+    X(Clay__arenaResetOffset) \
+    X(Clay__internalArena) \
+    X(Clay__layoutElements) \
+    X(Clay__renderCommands) \
+    X(Clay__openLayoutElementStack) \
+    X(Clay__layoutElementChildren) \
+    X(Clay__layoutElementChildrenBuffer) \
+    X(Clay__textElementData) \
+    X(Clay__imageElementPointers) \
+    X(Clay__reusableElementIndexBuffer) \
+    X(Clay__layoutElementClipElementIds) \
+    X(Clay__layoutConfigs) \
+    X(Clay__elementConfigBuffer) \
+    X(Clay__elementConfigs) \
+    X(Clay__rectangleElementConfigs) \
+    X(Clay__textElementConfigs) \
+    X(Clay__imageElementConfigs) \
+    X(Clay__floatingElementConfigs) \
+    X(Clay__scrollElementConfigs) \
+    X(Clay__customElementConfigs) \
+    X(Clay__borderElementConfigs) \
+    X(Clay__layoutElementIdStrings) \
+    X(Clay__wrappedTextLines) \
+    X(Clay__layoutElementTreeNodeArray1) \
+    X(Clay__layoutElementTreeRoots) \
+    X(Clay__layoutElementsHashMapInternal) \
+    X(Clay__layoutElementsHashMap) \
+    X(Clay__measureTextHashMapInternal) \
+    X(Clay__measureTextHashMapInternalFreeList) \
+    X(Clay__measureTextHashMap) \
+    X(Clay__measuredWords) \
+    X(Clay__measuredWordsFreeList) \
+    X(Clay__openClipElementStack) \
+    X(Clay__pointerOverIds) \
+    X(Clay__scrollContainerDatas) \
+    X(Clay__treeNodeVisited) \
+    X(Clay__dynamicStringData) \
+    X(Clay__debugElementData) \
+    X(Clay__MeasureText) \
+    X(Clay__QueryScrollOffset)
+
+size_t ClayRs_ContextStateSize(void) {
+    size_t size = 0;
+#define CLAY_RS_SIZE(variable) size += sizeof(variable);
+    CLAY_RS_CONTEXT_STATE(CLAY_RS_SIZE)
+#undef CLAY_RS_SIZE
+    return size;
+}
+
+void ClayRs_SaveContextState(char *state) {
+#define CLAY_RS_SAVE(variable) memcpy(state, &variable, sizeof(variable)); state += sizeof(variable);
+    CLAY_RS_CONTEXT_STATE(CLAY_RS_SAVE)
+#undef CLAY_RS_SAVE
+}
+
+void ClayRs_RestoreContextState(const char *state) {
+#define CLAY_RS_RESTORE(variable) memcpy(&variable, state, sizeof(variable)); state += sizeof(variable);
+    CLAY_RS_CONTEXT_STATE(CLAY_RS_RESTORE)
+#undef CLAY_RS_RESTORE
+}
+
+// Clay_MinMemorySize lays its arrays out in a fake arena through the globals, which
+// would leave the current context pointing at nothing
+uint32_t ClayRs_MinMemorySize(void) {
+    char *state = malloc(ClayRs_ContextStateSize());
+    ClayRs_SaveContextState(state);
+    uint32_t size = Clay_MinMemorySize();
+    ClayRs_RestoreContextState(state);
+    free(state);
+    return size;
+}
+
+// Id of the element currently being configured, like Clay_Hovered an id is
+// generated for anonymous elements
+Clay_ElementId ClayRs_GetOpenElementId(void) {
+    if (Clay__booleanWarnings.maxElementsExceeded) {
+        return CLAY__INIT(Clay_ElementId) {};
+    }
+    Clay_LayoutElement *openLayoutElement = Clay__GetOpenLayoutElement();
+    if (openLayoutElement->id == 0) {
+        Clay__GenerateIdForAnonymousElement(openLayoutElement);
+    }
+    return Clay__GetHashMapItem(openLayoutElement->id)->elementId;
+}
+
+Clay_PointerData ClayRs_GetPointerData(void) {
+    return Clay__pointerInfo;
+}
+
+// Like Clay_GetScrollContainerData, found is false for elements not declared in the last layout
+Clay_BoundingBox ClayRs_GetElementBoundingBox(Clay_ElementId id, bool *found) {
+    Clay_LayoutElementHashMapItem *hashMapItem = Clay__GetHashMapItem(id.id);
+    *found = hashMapItem != &CLAY__LAYOUT_ELEMENT_HASH_MAP_ITEM_DEFAULT && hashMapItem->generation > Clay__generation;
+    return hashMapItem->boundingBox;
+}
+
+// The id declared for an element, or an empty id if the hash is derived or unknown
+Clay_ElementId ClayRs_GetElementIdForHash(uint32_t id) {
+    Clay_LayoutElementHashMapItem *hashMapItem = Clay__GetHashMapItem(id);
+    if (hashMapItem == &CLAY__LAYOUT_ELEMENT_HASH_MAP_ITEM_DEFAULT || hashMapItem->generation <= Clay__generation) {
+        return CLAY__INIT(Clay_ElementId) {};
+    }
+    return hashMapItem->elementId;
+}
+
+// Measures with the function set by Clay_SetMeasureTextFunction, zero if there is none
+Clay_Dimensions ClayRs_MeasureText(Clay_String text, Clay_TextElementConfig *config) {
+    if (!Clay__MeasureText) {
+        return CLAY__INIT(Clay_Dimensions) {};
+    }
+    return Clay__MeasureText(&text, config);
+}
+
+// Bounds from the last layout while the next one is being declared, found is false
+// for elements that weren't declared in it
+Clay_BoundingBox ClayRs_GetPreviousBoundingBox(uint32_t id, bool *found) {
+    Clay_LayoutElementHashMapItem *hashMapItem = Clay__GetHashMapItem(id);
+    *found = hashMapItem != &CLAY__LAYOUT_ELEMENT_HASH_MAP_ITEM_DEFAULT && hashMapItem->generation >= Clay__generation;
+    return hashMapItem->boundingBox;
+}
+
+// Drops every cached text measurement. Entries are keyed on the string's pointer and
+// length, so this must run before a freed string's memory can be handed out again
+void ClayRs_ResetMeasureTextCache(void) {
+    for (int i = 0; i < Clay__measureTextHashMap.capacity; ++i) {
+        Clay__measureTextHashMap.internalArray[i] = 0;
+    }
+    Clay__measureTextHashMapInternal.length = 1;
+    Clay__measureTextHashMapInternalFreeList.length = 0;
+    Clay__measuredWords.length = 0;
+    Clay__measuredWordsFreeList.length = 0;
+}
+
+// Loads the initial values from clay.h into the globals, so an arena created while
+// another one was current doesn't inherit its configuration or state
+void ClayRs_ResetContextState(void) {
+#define CLAY_RS_RESET(variable) memset(&variable, 0, sizeof(variable));
+    CLAY_RS_CONTEXT_STATE(CLAY_RS_RESET)
+#undef CLAY_RS_RESET
+    Clay__warningsEnabled = true;
+    Clay__maxElementCount = 8192;
+    Clay__maxMeasureTextCacheWordCount = 16384;
+    Clay__errorHandler = CLAY__INIT(Clay_ErrorHandler) { .errorHandlerFunction = Clay__ErrorHandlerFunctionDefault };
+    // clay.h starts pressed this frame, which reads as a press on the first frame
+    Clay__pointerInfo = CLAY__INIT(Clay_PointerData) { .position = {-1, -1}, .state = CLAY_POINTER_DATA_RELEASED };
+    Clay__dynamicElementIndexBaseHash = CLAY__INIT(Clay_ElementId) { .id = 128476991, .stringId = { .length = 8, .chars = "Auto ID" } };
+    Clay__debugViewWidth = 400;
+    Clay__debugViewHighlightColor = CLAY__INIT(Clay_Color) { 168, 66, 28, 100 };
+}
@@ -21,10 +21,20 @@ pub struct String<'a> {
     _lifetime_marker: PhantomData<&'a c_char>,
 }
 
-impl String<'_> {
+impl<'a> String<'a> {
+    pub const fn new(s: &'a str) -> Self {
+        Self {
+            length: s.len() as c_int,
+            chars: s.as_ptr() as *const c_char,
+            _lifetime_marker: PhantomData,
+        }
+    }
     pub fn len(&self) -> usize {
         self.length as usize
     }
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
 }
 
 impl fmt::Debug for String<'_> {
@@ -37,24 +47,24 @@ impl fmt::Debug for String<'_> {
 impl<'a> From<&'a str> for String<'a> {
     #[inline]
     fn from(s: &'a str) -> String<'a> {
-        Self {
-            length: s.len() as c_int,
-            chars: s.as_ptr() as *const c_char,
-            _lifetime_marker: PhantomData,
-        }
+        Self::new(s)
     }
 }
 
 impl<'a> From<String<'a>> for &'a str {
     #[inline]
     fn from(s: String<'a>) -> &'a str {
+        // Empty clay strings may have null chars
+        if s.length == 0 {
+            return "";
+        }
         let bytes = unsafe { slice::from_raw_parts(s.chars as *const u8, s.length as usize) };
         std::str::from_utf8(bytes).unwrap_or("<invalid UTF-8")
     }
 }
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
 // clay: Clay_Dimensions
 pub struct Dimensions {
     pub width: c_float,
@@ -68,7 +78,7 @@ impl Dimensions {
 }
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
 // clay: Clay_Vector2
 pub struct Vector2 {
     pub x: c_float,
@@ -95,6 +105,21 @@ impl Color {
     }
 }
 
+// Key into a renderer's ImageRegistry, passed through clay as the image data pointer.
+// The default handle is no image
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ImageHandle(usize);
+
+impl ImageHandle {
+    pub fn from_index(index: usize) -> Self {
+        Self(index + 1)
+    }
+    pub fn index(&self) -> Option<usize> {
+        self.0.checked_sub(1)
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Default)]
 // clay: Clay_BoundingBox
@@ -123,6 +148,44 @@ pub struct PointerData {
     pub state: PointerDataInteractionState,
 }
 
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    // Command on macOS, the Windows key elsewhere
+    pub logo: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Key {
+    Tab,
+    Enter,
+    Escape,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Space,
+    // Letter and digit keys for shortcuts, lowercase, typed text comes from KeyEvent::Text
+    Character(char),
+}
+
+// Not part of clay, see Arena::set_key_events
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyEvent {
+    // Pressed or repeated
+    Key { key: Key, modifiers: Modifiers },
+    // Text typed, already translated by the keyboard layout
+    Text(char),
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 // clay: Clay_ScrollContainerData
@@ -135,7 +198,7 @@ pub struct ScrollContainerData<'a> {
 }
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
 // clay: Clay_CornerRadius
 pub struct CornerRadius {
     pub top_left: c_float,
@@ -272,6 +335,32 @@ impl SizingAxis {
             },
         }
     }
+    // clay: CLAY_SIZING_GROW({})
+    pub fn grow_unbounded() -> Self {
+        Self::grow(0., f32::MAX)
+    }
+    // clay: CLAY_SIZING_FIT({})
+    pub fn fit_unbounded() -> Self {
+        Self::fit(0., f32::MAX)
+    }
+}
+
+// Short SizingAxis constructors, Layout::new().width(grow()).height(fixed(60.))
+pub mod sizing {
+    use super::SizingAxis;
+
+    pub fn grow() -> SizingAxis {
+        SizingAxis::grow_unbounded()
+    }
+    pub fn fit() -> SizingAxis {
+        SizingAxis::fit_unbounded()
+    }
+    pub fn fixed(size: f32) -> SizingAxis {
+        SizingAxis::fixed(size)
+    }
+    pub fn percent(percent: f32) -> SizingAxis {
+        SizingAxis::percent(percent)
+    }
 }
 
 impl Default for SizingAxis {
@@ -306,6 +395,19 @@ pub struct Sizing {
     pub height: SizingAxis,
 }
 
+impl Sizing {
+    pub fn new(width: SizingAxis, height: SizingAxis) -> Self {
+        Self { width, height }
+    }
+    // Grow to fill the parent on both axes
+    pub fn expand() -> Self {
+        Self::new(SizingAxis::grow_unbounded(), SizingAxis::grow_unbounded())
+    }
+    pub fn fixed(width: f32, height: f32) -> Self {
+        Self::new(SizingAxis::fixed(width), SizingAxis::fixed(height))
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Default)]
 // clay: Clay_Padding
@@ -314,8 +416,17 @@ pub struct Padding {
     pub y: u16,
 }
 
+impl Padding {
+    pub fn new(x: u16, y: u16) -> Self {
+        Self { x, y }
+    }
+    pub fn all(padding: u16) -> Self {
+        Self::new(padding, padding)
+    }
+}
+
 #[repr(C)]
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
 // clay: Clay_TextElementConfigWrapMode
 pub enum TextWrapMode {
     #[default]
@@ -1,6 +1,6 @@
 use crate::data;
 use crate::system::{
-    ArenaInternal, ElementConfigType, ElementConfigUnion, ErrorData, ErrorHandler,
+    ArenaInternal, ElementConfigType, ElementConfigUnion, ErrorData, ErrorHandlerInternal,
     MeasureTextCallback, QueryScrollOffsetCallback, RenderCommand, RenderCommandArray,
 };
 use crate::ui;
@@ -10,15 +10,14 @@ pub(crate) type OnHoverCallback = unsafe extern "C" fn(ui::ElementId, data::Poin
 
 #[link(name = "clay")]
 extern "C" {
-    pub(crate) fn Clay_MinMemorySize() -> u32;
     pub(crate) fn Clay_CreateArenaWithCapacityAndMemory(
         capacity: u32,
-        offset: *const c_void,
+        offset: *mut c_void,
     ) -> ArenaInternal;
     pub(crate) fn Clay_Initialize(
         arena: ArenaInternal,
         layout_dimensions: data::Dimensions,
-        error_handler: ErrorHandler,
+        error_handler: ErrorHandlerInternal,
     );
     pub(crate) fn Clay_SetPointerState(position: data::Vector2, pointer_down: bool);
     pub(crate) fn Clay_UpdateScrollContainers(
@@ -31,11 +30,14 @@ extern "C" {
     pub(crate) fn Clay_Hovered() -> bool;
     pub(crate) fn Clay_OnHover(on_hover_callback: OnHoverCallback, user_data: isize);
     pub(crate) fn Clay_PointerOver(element_id: ui::ElementId) -> bool;
-    pub(crate) fn Clay_GetScrollContainerData(id: ui::ElementId) -> data::ScrollContainerData;
+    pub(crate) fn Clay_GetScrollContainerData<'a>(
+        id: ui::ElementId,
+    ) -> data::ScrollContainerData<'a>;
     pub(crate) fn Clay_SetQueryScrollOffsetFunction(
         query_scroll_offset_callback: QueryScrollOffsetCallback,
     );
     pub(crate) fn Clay_SetCullingEnabled(enabled: bool);
+    pub(crate) fn Clay_SetDebugModeEnabled(enabled: bool);
     pub(crate) fn Clay_SetMaxElementCount(max_element_count: u32);
     pub(crate) fn Clay_SetMaxMeasureTextCacheWordCount(max_measure_text_cache_word_count: u32);
 
@@ -69,4 +71,22 @@ extern "C" {
         array: &RenderCommandArray<'a>,
         index: i32,
     ) -> &'a RenderCommand<'a>;
+
+    // clay.c
+    pub(crate) fn ClayRs_ContextStateSize() -> usize;
+    pub(crate) fn ClayRs_ResetContextState();
+    pub(crate) fn ClayRs_MinMemorySize() -> u32;
+    pub(crate) fn ClayRs_SaveContextState(state: *mut u8);
+    pub(crate) fn ClayRs_RestoreContextState(state: *const u8);
+    pub(crate) fn ClayRs_GetOpenElementId<'a>() -> ui::ElementId<'a>;
+    pub(crate) fn ClayRs_GetPointerData() -> data::PointerData;
+    #[cfg(feature = "testing")]
+    pub(crate) fn ClayRs_GetElementIdForHash<'a>(id: u32) -> ui::ElementId<'a>;
+    pub(crate) fn ClayRs_GetElementBoundingBox(
+        id: ui::ElementId,
+        found: *mut bool,
+    ) -> data::BoundingBox;
+    pub(crate) fn ClayRs_MeasureText(text: data::String, config: &ui::Text) -> data::Dimensions;
+    pub(crate) fn ClayRs_GetPreviousBoundingBox(id: u32, found: *mut bool) -> data::BoundingBox;
+    pub(crate) fn ClayRs_ResetMeasureTextCache();
 }
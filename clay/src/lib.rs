@@ -5,7 +5,12 @@ pub mod prelude {
 mod data;
 mod external;
 mod system;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod text_input;
 mod ui;
 pub use crate::data::*;
 pub use crate::system::*;
+pub use crate::text_input::*;
 pub use crate::ui::*;
+pub use clay_macros::clay;
@@ -1,16 +1,72 @@
-use crate::{data, external, ui};
+use crate::{data, external, text_input, ui};
 use clay_macros::packed_enum;
 use std::{
+    alloc,
+    any::Any,
+    cell::{Cell, Ref, RefCell, RefMut},
+    collections::{HashMap, HashSet},
     fmt,
     marker::PhantomData,
-    os::raw::{c_int, c_void},
+    ops::Range,
+    os::raw::c_void,
+    ptr::NonNull,
+    sync::{
+        atomic::{AtomicPtr, Ordering},
+        Arc, Mutex,
+    },
 };
 
 pub type MeasureTextCallback = extern "C" fn(&data::String, &ui::Text) -> data::Dimensions;
+
+pub trait TextMeasurer {
+    fn measure_text(&mut self, text: &str, config: &ui::Text) -> data::Dimensions;
+}
+
+impl<F> TextMeasurer for F
+where
+    F: FnMut(&str, &ui::Text) -> data::Dimensions,
+{
+    fn measure_text(&mut self, text: &str, config: &ui::Text) -> data::Dimensions {
+        self(text, config)
+    }
+}
+
+thread_local! {
+    // Clay_SetMeasureTextFunction takes no userdata, so the measurer of the
+    // arena currently being laid out is published here for the trampoline
+    static TEXT_MEASURER: Cell<Option<NonNull<dyn TextMeasurer>>> = const { Cell::new(None) };
+}
+
+extern "C" fn measure_text_trampoline(text: &data::String, config: &ui::Text) -> data::Dimensions {
+    match TEXT_MEASURER.with(Cell::get) {
+        Some(mut measurer) => unsafe { measurer.as_mut() }.measure_text((*text).into(), config),
+        None => data::default(),
+    }
+}
+
+// Installs a measurer for the duration of a layout, restoring the previous one on drop
+struct TextMeasurerScope(Option<NonNull<dyn TextMeasurer>>);
+
+impl TextMeasurerScope {
+    fn install(measurer: Option<&mut (dyn TextMeasurer + '_)>) -> Self {
+        let measurer = measurer.map(|measurer| unsafe {
+            std::mem::transmute::<NonNull<dyn TextMeasurer + '_>, NonNull<dyn TextMeasurer>>(
+                NonNull::from(measurer),
+            )
+        });
+        Self(TEXT_MEASURER.with(|current| current.replace(measurer)))
+    }
+}
+
+impl Drop for TextMeasurerScope {
+    fn drop(&mut self) {
+        TEXT_MEASURER.with(|current| current.set(self.0));
+    }
+}
 pub type QueryScrollOffsetCallback = extern "C" fn(u32) -> data::Vector2;
 
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 // clay: Clay_ErrorType
 pub enum ErrorType {
     TextMeasurementFunctionNotProvided,
@@ -30,28 +86,433 @@ pub type ErrorHandlerCallback<'a> = extern "C" fn(ErrorData<'a>);
 pub struct ErrorData<'a> {
     error_type: ErrorType,
     error_text: data::String<'a>,
-    user_data: *const c_int,
+    user_data: usize,
 }
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 // clay: Clay_ErrorHandler
-pub struct ErrorHandler<'a> {
+pub(crate) struct ErrorHandlerInternal<'a> {
     error_handler_callback: ErrorHandlerCallback<'a>,
-    user_data: *const c_int,
+    user_data: usize,
 }
 
-extern "C" fn default_error_handler(error_data: ErrorData<'_>) {
-    unsafe {
-        external::Clay__ErrorHandlerFunctionDefault(error_data);
+#[derive(Debug, Copy, Clone)]
+pub struct ClayError<'a> {
+    pub kind: ErrorType,
+    pub message: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedClayError {
+    pub kind: ErrorType,
+    pub message: std::string::String,
+}
+
+impl From<ClayError<'_>> for OwnedClayError {
+    fn from(error: ClayError<'_>) -> Self {
+        Self {
+            kind: error.kind,
+            message: error.message.to_owned(),
+        }
     }
 }
 
-impl Default for ErrorHandler<'_> {
-    fn default() -> Self {
+type ErrorCallback<'a> = Box<dyn FnMut(ClayError<'_>) + 'a>;
+
+// Without a callback, errors are forwarded to Clay__ErrorHandlerFunctionDefault
+#[derive(Default)]
+pub struct ErrorHandler<'a> {
+    callback: Option<ErrorCallback<'a>>,
+    collect: bool,
+}
+
+impl<'a> ErrorHandler<'a> {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: FnMut(ClayError<'_>) + 'a,
+    {
+        Self {
+            callback: Some(Box::new(callback)),
+            collect: false,
+        }
+    }
+    // Log errors so they are returned from Arena::render
+    pub fn collect() -> Self {
+        Self {
+            callback: None,
+            collect: true,
+        }
+    }
+    pub fn collecting(self) -> Self {
+        Self {
+            collect: true,
+            ..self
+        }
+    }
+}
+
+impl fmt::Debug for ErrorHandler<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorHandler")
+            .field("callback", &self.callback.is_some())
+            .field("collect", &self.collect)
+            .finish()
+    }
+}
+
+// Passed to clay as ErrorHandler user_data
+#[derive(Debug, Default)]
+struct ErrorState<'a> {
+    handler: ErrorHandler<'a>,
+    errors: Vec<OwnedClayError>,
+}
+
+extern "C" fn error_handler_trampoline(error_data: ErrorData<'_>) {
+    let state = unsafe { &mut *(error_data.user_data as *mut ErrorState) };
+    let error = ClayError {
+        kind: error_data.error_type,
+        message: error_data.error_text.into(),
+    };
+    if state.handler.collect {
+        state.errors.push(error.into());
+    }
+    match state.handler.callback.as_mut() {
+        Some(callback) => callback(error),
+        None => unsafe { external::Clay__ErrorHandlerFunctionDefault(error_data) },
+    }
+}
+
+pub(crate) type HoverHandler<'a> = Box<dyn FnMut(ui::ElementId, data::PointerData) + 'a>;
+pub(crate) type PressHandler<'a> = Box<dyn FnMut(ui::ElementId, data::PointerData) + 'a>;
+
+// Where the current press started, kept by the Arena across frames
+#[derive(Debug, Default)]
+pub(crate) struct PressState {
+    position: data::Vector2,
+    element_ids: Vec<u32>,
+}
+
+impl PressState {
+    fn update(&mut self, pointer_data: &data::PointerData) {
+        match pointer_data.state {
+            data::PointerDataInteractionState::PressedThisFrame => {
+                self.position = pointer_data.position;
+                self.element_ids.clear();
+            }
+            data::PointerDataInteractionState::Released => self.element_ids.clear(),
+            _ => {}
+        }
+    }
+}
+
+// Keyboard focus, kept by the Arena across frames
+#[derive(Debug, Default)]
+pub(crate) struct FocusState {
+    focused: Option<u32>,
+    // Focusable elements in the order they were declared last frame
+    order: Vec<u32>,
+    // The part of order declared in Builder::trap_focus
+    trap: Option<Range<usize>>,
+}
+
+impl FocusState {
+    fn focusable(&self) -> &[u32] {
+        match &self.trap {
+            Some(trap) => &self.order[trap.clone()],
+            None => &self.order,
+        }
+    }
+
+    // Tab and Shift-Tab move through last frame's focusable elements
+    fn traverse(&mut self, key_events: &[data::KeyEvent]) {
+        // Like focus set by the application outside last frame's trap
+        if self.trap.is_some()
+            && self
+                .focused
+                .is_some_and(|focused| !self.focusable().contains(&focused))
+        {
+            self.focused = None;
+        }
+        for key_event in key_events {
+            let data::KeyEvent::Key {
+                key: data::Key::Tab,
+                modifiers,
+            } = key_event
+            else {
+                continue;
+            };
+            let order = self.focusable();
+            let count = order.len();
+            if count == 0 {
+                continue;
+            }
+            let index = self
+                .focused
+                .and_then(|focused| order.iter().position(|&id| id == focused));
+            let next = match (index, modifiers.shift) {
+                (Some(index), false) => (index + 1) % count,
+                (Some(index), true) => (index + count - 1) % count,
+                (None, false) => 0,
+                (None, true) => count - 1,
+            };
+            self.focused = Some(order[next]);
+        }
+    }
+}
+
+// Used by TextInput to copy, cut and paste, see Arena::set_clipboard
+pub trait Clipboard {
+    fn get_text(&mut self) -> Option<std::string::String>;
+    fn set_text(&mut self, text: &str);
+}
+
+// Only shared by the inputs of one arena, the default
+impl Clipboard for std::string::String {
+    fn get_text(&mut self) -> Option<std::string::String> {
+        Some(self.clone())
+    }
+    fn set_text(&mut self, text: &str) {
+        text.clone_into(self);
+    }
+}
+
+// Text copied in by Builder::store_text, kept by the Arena until the frame is rendered.
+// Clay caches measurements by pointer and length and only evicts them lazily, so
+// strings that go unused are retired rather than freed, and retired strings are
+// only freed together with clay's measure cache once they add up
+#[derive(Debug, Default)]
+pub(crate) struct TextStorage {
+    // Last frame each string was used in
+    strings: HashMap<Box<str>, Cell<u64>>,
+    retired: Vec<Box<str>>,
+    retired_bytes: usize,
+    frame: u64,
+}
+
+impl TextStorage {
+    const RETIRED_BYTES_LIMIT: usize = 64 * 1024;
+
+    // Must be called with the arena current, as it may reset clay's measure cache
+    fn next_frame(&mut self) {
+        self.frame += 1;
+        let frame = self.frame;
+        for (text, used) in std::mem::take(&mut self.strings) {
+            if frame - used.get() <= 2 {
+                self.strings.insert(text, used);
+            } else {
+                self.retired_bytes += text.len();
+                self.retired.push(text);
+            }
+        }
+        if self.retired_bytes > Self::RETIRED_BYTES_LIMIT {
+            unsafe { external::ClayRs_ResetMeasureTextCache() };
+            self.retired.clear();
+            self.retired_bytes = 0;
+        }
+    }
+
+    fn store(&mut self, text: &str) -> *const str {
+        if let Some((stored, used)) = self.strings.get_key_value(text) {
+            used.set(self.frame);
+            return &**stored;
+        }
+        let stored: Box<str> = text.into();
+        let ptr: *const str = &*stored;
+        self.strings.insert(stored, Cell::new(self.frame));
+        ptr
+    }
+}
+
+// Payloads of ui::Custom elements, boxed so clay gets a thin pointer and shared
+// with the OwnedRenderCommands copied from them
+pub(crate) type CustomPayload = Arc<dyn Any + Send + Sync>;
+pub(crate) type CustomData = Vec<Box<CustomPayload>>;
+
+// Storage for the duration of one Arena::render
+#[derive(Default)]
+pub(crate) struct FrameStorage<'a> {
+    hover_handlers: RefCell<Vec<(ui::ElementId<'a>, HoverHandler<'a>)>>,
+    press_handlers: RefCell<Vec<(ui::ElementId<'a>, PressHandler<'a>)>>,
+    press_state: RefCell<PressState>,
+    text_storage: RefCell<TextStorage>,
+    custom_data: RefCell<CustomData>,
+    focus_state: RefCell<FocusState>,
+    // Focusable elements declared this frame
+    focus_order: RefCell<Vec<u32>>,
+    // A focusable element was pressed this frame
+    focus_pressed: Cell<bool>,
+    focus_trap: RefCell<Option<Range<usize>>>,
+    key_events: Vec<data::KeyEvent>,
+    // Last frame's states, taken by the inputs declared this frame
+    previous_text_inputs: RefCell<text_input::TextInputStates>,
+    text_inputs: RefCell<text_input::TextInputStates>,
+    clipboard: RefCell<Option<Box<dyn Clipboard>>>,
+}
+
+impl<'a> FrameStorage<'a> {
+    fn new(
+        press_state: PressState,
+        text_storage: TextStorage,
+        custom_data: CustomData,
+        focus_state: FocusState,
+        key_events: Vec<data::KeyEvent>,
+        text_inputs: text_input::TextInputStates,
+        clipboard: Option<Box<dyn Clipboard>>,
+    ) -> Self {
         Self {
-            error_handler_callback: default_error_handler,
-            user_data: std::ptr::null(),
+            press_state: RefCell::new(press_state),
+            text_storage: RefCell::new(text_storage),
+            custom_data: RefCell::new(custom_data),
+            focus_state: RefCell::new(focus_state),
+            key_events,
+            previous_text_inputs: RefCell::new(text_inputs),
+            clipboard: RefCell::new(clipboard),
+            ..Default::default()
+        }
+    }
+
+    // States of inputs that aren't declared this frame are dropped
+    pub(crate) fn take_text_input_state(&self, id: u32) -> text_input::TextInputState {
+        self.previous_text_inputs
+            .borrow_mut()
+            .remove(&id)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_text_input_state(&self, id: u32, state: text_input::TextInputState) {
+        self.text_inputs.borrow_mut().insert(id, state);
+    }
+
+    pub(crate) fn clipboard(&self) -> RefMut<'_, dyn Clipboard> {
+        RefMut::map(self.clipboard.borrow_mut(), |clipboard| {
+            clipboard.as_deref_mut().unwrap()
+        })
+    }
+
+    // Pressing on a focusable element focuses it, the innermost one if nested
+    pub(crate) fn set_focusable(&self, id: u32, hovered: bool) {
+        self.focus_order.borrow_mut().push(id);
+        let pointer_data = unsafe { external::ClayRs_GetPointerData() };
+        if hovered && pointer_data.state == data::PointerDataInteractionState::PressedThisFrame {
+            self.focus_state.borrow_mut().focused = Some(id);
+            self.focus_pressed.set(true);
+        }
+    }
+
+    pub(crate) fn is_focused(&self, id: u32) -> bool {
+        self.focus_state.borrow().focused == Some(id)
+    }
+
+    pub(crate) fn key_events(&self) -> &[data::KeyEvent] {
+        &self.key_events
+    }
+
+    pub(crate) fn focus_order_len(&self) -> usize {
+        self.focus_order.borrow().len()
+    }
+
+    // The trap started last wins, so a nested or later declared one
+    pub(crate) fn trap_focus(&self, start: usize) {
+        let mut focus_trap = self.focus_trap.borrow_mut();
+        if focus_trap.as_ref().is_none_or(|trap| start > trap.start) {
+            *focus_trap = Some(start..self.focus_order.borrow().len());
+        }
+    }
+
+    // Pressing outside every focusable element, or not declaring the focused
+    // element or declaring it outside the focus trap, clears the focus
+    fn finish_focus(&self) -> FocusState {
+        let mut focus_state = self.focus_state.take();
+        focus_state.order = self.focus_order.take();
+        focus_state.trap = self.focus_trap.take();
+        let pointer_data = unsafe { external::ClayRs_GetPointerData() };
+        let pressed_elsewhere = pointer_data.state
+            == data::PointerDataInteractionState::PressedThisFrame
+            && !self.focus_pressed.get();
+        if pressed_elsewhere
+            || focus_state
+                .focused
+                .is_some_and(|focused| !focus_state.focusable().contains(&focused))
+        {
+            focus_state.focused = None;
+        }
+        focus_state
+    }
+
+    // Kept by the Arena until the next frame is laid out, and by the OwnedRenderCommands
+    // copied from this frame, see RenderCommand::custom
+    pub(crate) fn store_custom(&self, data: CustomPayload) -> *const c_void {
+        let data = Box::new(data);
+        let ptr = &*data as *const CustomPayload as *const c_void;
+        self.custom_data.borrow_mut().push(data);
+        ptr
+    }
+
+    // Strings are only removed by TextStorage::next_frame, after this frame is rendered
+    pub(crate) fn store_text(&self, text: &str) -> data::String<'_> {
+        let stored = self.text_storage.borrow_mut().store(text);
+        data::String::new(unsafe { &*stored })
+    }
+
+    pub(crate) fn interaction(&self, id: u32, hovered: bool) -> ui::Interaction {
+        let pointer_data = unsafe { external::ClayRs_GetPointerData() };
+        let mut press_state = self.press_state.borrow_mut();
+        let state = pointer_data.state;
+        if state == data::PointerDataInteractionState::PressedThisFrame
+            && hovered
+            && !press_state.element_ids.contains(&id)
+        {
+            press_state.element_ids.push(id);
+        }
+        let pressed_here = press_state.element_ids.contains(&id);
+        let down = matches!(
+            state,
+            data::PointerDataInteractionState::PressedThisFrame
+                | data::PointerDataInteractionState::Pressed
+        );
+        let released =
+            pressed_here && state == data::PointerDataInteractionState::ReleasedThisFrame;
+        let drag_delta = if pressed_here && (down || released) {
+            data::Vector2 {
+                x: pointer_data.position.x - press_state.position.x,
+                y: pointer_data.position.y - press_state.position.y,
+            }
+        } else {
+            data::Vector2::default()
+        };
+        ui::Interaction {
+            hovered,
+            pressed: pressed_here && down,
+            clicked: released && hovered,
+            released,
+            drag_delta,
+        }
+    }
+
+    pub(crate) fn add_hover_handler(&self, id: ui::ElementId<'a>, handler: HoverHandler<'a>) {
+        self.hover_handlers.borrow_mut().push((id, handler));
+    }
+
+    pub(crate) fn add_press_handler(&self, id: ui::ElementId<'a>, handler: PressHandler<'a>) {
+        self.press_handlers.borrow_mut().push((id, handler));
+    }
+
+    // Like clay's hover callbacks, but run after Clay_EndLayout, presses only on the frame they start
+    fn dispatch_pointer_handlers(&self) {
+        let pointer_data = unsafe { external::ClayRs_GetPointerData() };
+        for (id, handler) in self.hover_handlers.borrow_mut().iter_mut() {
+            if id.is_pointer_over() {
+                handler(*id, pointer_data);
+            }
+        }
+        if pointer_data.state != data::PointerDataInteractionState::PressedThisFrame {
+            return;
+        }
+        for (id, handler) in self.press_handlers.borrow_mut().iter_mut() {
+            if id.is_pointer_over() {
+                handler(*id, pointer_data);
+            }
         }
     }
 }
@@ -59,6 +520,61 @@ impl Default for ErrorHandler<'_> {
 pub trait Renderer {
     fn prepare_frame(&self) -> data::Dimensions;
     fn render(&self, render_commands: &mut RenderCommandIter<'_>);
+    // Key events since the last frame, taken by Arena::render after prepare_frame
+    fn key_events(&self) -> Vec<data::KeyEvent> {
+        Vec::new()
+    }
+}
+
+// Implemented by renderers that own images, like fonts images are added up front
+// and referenced by handle from clay::Image
+pub trait ImageRegistry {
+    type Image;
+
+    fn images(&self) -> &Images<Self::Image>;
+    // Source size of an image, None if it isn't known
+    fn dimensions(image: &Self::Image) -> Option<data::Dimensions>;
+
+    fn add_image(&self, image: Self::Image) -> data::ImageHandle {
+        self.images().add(image)
+    }
+    // None if the handle isn't registered or the size isn't known
+    fn image_dimensions(&self, handle: data::ImageHandle) -> Option<data::Dimensions> {
+        Self::dimensions(&*self.images().get(handle)?)
+    }
+
+    // An Image element with the registered image's source_dimensions
+    fn image(&self, handle: data::ImageHandle) -> ui::Image {
+        ui::Image::new(handle, self.image_dimensions(handle).unwrap_or_default())
+    }
+}
+
+// The images of an ImageRegistry, a handle is the index an image was added at
+#[derive(Debug)]
+pub struct Images<T> {
+    images: RefCell<Vec<T>>,
+}
+
+impl<T> Images<T> {
+    pub fn new() -> Self {
+        Self {
+            images: RefCell::new(Vec::new()),
+        }
+    }
+    pub fn add(&self, image: T) -> data::ImageHandle {
+        let mut images = self.images.borrow_mut();
+        images.push(image);
+        data::ImageHandle::from_index(images.len() - 1)
+    }
+    pub fn get(&self, handle: data::ImageHandle) -> Option<Ref<'_, T>> {
+        Ref::filter_map(self.images.borrow(), |images| images.get(handle.index()?)).ok()
+    }
+}
+
+impl<T> Default for Images<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[repr(C)]
@@ -70,31 +586,178 @@ pub(crate) struct ArenaInternal {
     memory: *mut c_void,
 }
 
-#[repr(C)]
+// Clay pads allocations relative to the absolute address, so memory aligned
+// beyond any clay type needs exactly Clay_MinMemorySize bytes
+const ARENA_ALIGNMENT: usize = 64;
+
 #[derive(Debug)]
+enum ArenaMemory<'a> {
+    Borrowed(PhantomData<&'a mut [u8]>),
+    Owned(alloc::Layout),
+}
+
+#[derive(Debug, Default)]
+pub struct ArenaOptions<'a> {
+    pub max_element_count: Option<u32>,
+    pub max_measure_text_cache_word_count: Option<u32>,
+    pub error_handler: ErrorHandler<'a>,
+}
+
+// Saved state of the initialized arena whose context is loaded into clay's globals,
+// null if the globals are not owned by any arena
+static CURRENT_CONTEXT: AtomicPtr<u8> = AtomicPtr::new(std::ptr::null_mut());
+// Saved states of all initialized arenas
+static LIVE_CONTEXTS: Mutex<Option<HashSet<usize>>> = Mutex::new(None);
+
+fn is_live_context(state: *mut u8) -> bool {
+    LIVE_CONTEXTS
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|live| live.contains(&(state as usize)))
+}
+
+// Saves clay's globals into the current context and leaves them unowned, with
+// clay's defaults if they were owned
+fn detach_context() {
+    let current = CURRENT_CONTEXT.swap(std::ptr::null_mut(), Ordering::SeqCst);
+    if !current.is_null() {
+        unsafe {
+            external::ClayRs_SaveContextState(current);
+            external::ClayRs_ResetContextState();
+        }
+    }
+}
+
+fn switch_context(state: *mut u8) {
+    let current = CURRENT_CONTEXT.swap(state, Ordering::SeqCst);
+    if current != state {
+        unsafe {
+            if !current.is_null() {
+                external::ClayRs_SaveContextState(current);
+            }
+            external::ClayRs_RestoreContextState(state);
+        }
+    }
+}
+
+// Switches back to the previously current context on drop
+struct ContextScope(*mut u8);
+
+impl Drop for ContextScope {
+    fn drop(&mut self) {
+        if !self.0.is_null() && is_live_context(self.0) {
+            switch_context(self.0);
+        }
+    }
+}
+
+#[repr(C)]
 pub struct Arena<'a> {
-    memory: &'a [u8],
+    memory: ArenaMemory<'a>,
     internal: ArenaInternal,
     render_commands: RenderCommandIter<'a>,
+    text_measurer: Option<Box<dyn TextMeasurer + 'a>>,
+    // Owned, freed on drop - clay holds a pointer to it
+    error_state: NonNull<ErrorState<'a>>,
+    // Owned, freed on drop - clay's globals are saved here while another context is current
+    context_state: NonNull<[u8]>,
+    initialized: bool,
+    press_state: PressState,
+    text_storage: TextStorage,
+    custom_data: CustomData,
+    focus_state: FocusState,
+    // Set by set_key_events and Renderer::key_events, taken by the next frame laid out
+    key_events: Vec<data::KeyEvent>,
+    text_inputs: text_input::TextInputStates,
+    // Always set outside of layout_frame
+    clipboard: Option<Box<dyn Clipboard>>,
+}
+
+impl fmt::Debug for Arena<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Arena")
+            .field("memory", &self.memory)
+            .field("internal", &self.internal)
+            .field("render_commands", &self.render_commands)
+            .field("error_state", unsafe { self.error_state.as_ref() })
+            .finish_non_exhaustive()
+    }
 }
 
 impl<'a> Arena<'a> {
     // clay: Clay_CreateArenaWithCapacityAndMemory
-    pub fn new(memory: &'a [u8]) -> Arena<'a> {
+    pub fn new(memory: &'a mut [u8]) -> Arena<'a> {
+        Self::from_memory(
+            ArenaMemory::Borrowed(PhantomData),
+            memory.len(),
+            memory.as_mut_ptr(),
+        )
+    }
+    // clay: Clay_CreateArenaWithCapacityAndMemory/Clay_Initialize
+    pub fn with_capacity(layout_dimensions: data::Dimensions, options: ArenaOptions<'a>) -> Self {
+        // Don't configure the current context, start from clay's defaults
+        detach_context();
+        unsafe { external::ClayRs_ResetContextState() };
+        if let Some(max_element_count) = options.max_element_count {
+            Arena::set_max_element_count(max_element_count);
+        }
+        if let Some(max_measure_text_cache_word_count) = options.max_measure_text_cache_word_count {
+            Arena::set_max_measure_text_cache_word_count(max_measure_text_cache_word_count);
+        }
+        let layout =
+            alloc::Layout::from_size_align(Arena::min_memory_size() as usize, ARENA_ALIGNMENT)
+                .unwrap();
+        let memory = unsafe { alloc::alloc_zeroed(layout) };
+        if memory.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+        let mut arena = Self::from_memory(ArenaMemory::Owned(layout), layout.size(), memory);
+        arena.initialize(layout_dimensions, options.error_handler);
+        arena
+    }
+    fn from_memory(memory: ArenaMemory<'a>, capacity: usize, ptr: *mut u8) -> Self {
         Arena {
             memory,
             internal: unsafe {
-                external::Clay_CreateArenaWithCapacityAndMemory(
-                    memory.len() as u32,
-                    memory.as_ptr() as *const c_void,
-                )
+                external::Clay_CreateArenaWithCapacityAndMemory(capacity as u32, ptr as *mut c_void)
             },
             render_commands: Default::default(),
+            text_measurer: None,
+            error_state: NonNull::from(Box::leak(Box::default())),
+            context_state: NonNull::from(Box::leak(
+                vec![0u8; unsafe { external::ClayRs_ContextStateSize() }].into_boxed_slice(),
+            )),
+            initialized: false,
+            press_state: PressState::default(),
+            text_storage: TextStorage::default(),
+            custom_data: CustomData::default(),
+            focus_state: FocusState::default(),
+            key_events: Vec::new(),
+            text_inputs: text_input::TextInputStates::default(),
+            clipboard: Some(Box::<std::string::String>::default()),
         }
     }
+    fn context_state_ptr(&self) -> *mut u8 {
+        self.context_state.as_ptr() as *mut u8
+    }
+    // Loads this arena's state into clay's globals, saving the current context's.
+    // An uninitialized arena just takes over the globals so it can be configured
+    fn make_current(&self) {
+        if self.initialized {
+            switch_context(self.context_state_ptr());
+        } else {
+            detach_context();
+        }
+    }
+    fn enter(&self) -> ContextScope {
+        let previous = CURRENT_CONTEXT.load(Ordering::SeqCst);
+        self.make_current();
+        ContextScope(previous)
+    }
     // clay: Clay_MinMemorySize
     pub fn min_memory_size() -> u32 {
-        unsafe { external::Clay_MinMemorySize() }
+        unsafe { external::ClayRs_MinMemorySize() }
     }
     // clay: Clay_SetMaxElementCount
     pub fn set_max_element_count(max_element_count: u32) {
@@ -107,11 +770,41 @@ impl<'a> Arena<'a> {
         };
     }
     // clay: Clay_Initialize
-    pub fn initialize(&self, layout_dimensions: data::Dimensions, error_handler: ErrorHandler) {
+    pub fn initialize(
+        &mut self,
+        layout_dimensions: data::Dimensions,
+        error_handler: ErrorHandler<'a>,
+    ) {
+        unsafe { self.error_state.as_mut() }.handler = error_handler;
+        let error_handler = ErrorHandlerInternal {
+            error_handler_callback: error_handler_trampoline,
+            user_data: self.error_state.as_ptr() as usize,
+        };
+        if self.initialized {
+            self.make_current();
+        } else {
+            detach_context();
+            self.initialized = true;
+            LIVE_CONTEXTS
+                .lock()
+                .unwrap()
+                .get_or_insert_with(HashSet::new)
+                .insert(self.context_state_ptr() as usize);
+            CURRENT_CONTEXT.store(self.context_state_ptr(), Ordering::SeqCst);
+        }
         unsafe { external::Clay_Initialize(self.internal, layout_dimensions, error_handler) }
     }
     // clay: Clay_SetMeasureTextFunction
-    pub fn set_measure_text_callback(callback: MeasureTextCallback) {
+    pub fn set_measure_text_callback<M>(&mut self, measurer: M)
+    where
+        M: TextMeasurer + 'a,
+    {
+        self.text_measurer = Some(Box::new(measurer));
+        self.make_current();
+        unsafe { external::Clay_SetMeasureTextFunction(measure_text_trampoline) };
+    }
+    // clay: Clay_SetMeasureTextFunction
+    pub fn set_measure_text_callback_raw(callback: MeasureTextCallback) {
         unsafe { external::Clay_SetMeasureTextFunction(callback) };
     }
     // clay: Clay_SetQueryScrollOffsetFunction
@@ -120,27 +813,269 @@ impl<'a> Arena<'a> {
     ) {
         unsafe { external::Clay_SetQueryScrollOffsetFunction(query_scroll_offset_callback) };
     }
+    // clay: Clay_SetCullingEnabled
+    pub fn set_culling_enabled(enabled: bool) {
+        unsafe { external::Clay_SetCullingEnabled(enabled) };
+    }
+    // clay: Clay_SetDebugModeEnabled
+    // Clay's inspector is laid out beside the UI using font 0, hovering a row
+    // highlights its element and clicking it expands or collapses its children
+    pub fn set_debug_mode(&self, enabled: bool) {
+        self.make_current();
+        unsafe { external::Clay_SetDebugModeEnabled(enabled) };
+    }
+    // Key events for the next frame this arena lays out, followed by the renderer's
+    // own with Arena::render. Tab and Shift-Tab move the focus, see Builder::set_focusable
+    pub fn set_key_events(&mut self, key_events: &[data::KeyEvent]) {
+        self.key_events = key_events.to_vec();
+    }
+    // The focused element's ElementId::id
+    pub fn focused(&self) -> Option<u32> {
+        self.focus_state.focused
+    }
+    pub fn set_focus(&mut self, id: Option<u32>) {
+        self.focus_state.focused = id;
+    }
+    // Used by TextInput, by default text is only copied between the arena's inputs
+    pub fn set_clipboard(&mut self, clipboard: impl Clipboard + 'static) {
+        self.clipboard = Some(Box::new(clipboard));
+    }
     // clay: Clay_SetLayoutDimensions
     fn set_layout_dimensions(dimensions: data::Dimensions) {
         unsafe { external::Clay_SetLayoutDimensions(dimensions) };
     }
 
     // clay: Clay_BeginLayout/Clay_EndLayout
-    pub fn render<F>(&mut self, renderer: &impl Renderer, ui: F)
+    fn layout_frame<'f, F>(&mut self, ui: F)
     where
-        F: FnOnce(&ui::Builder),
+        F: FnOnce(&ui::Builder<'f>),
     {
-        Arena::set_layout_dimensions(renderer.prepare_frame());
+        let _measurer = TextMeasurerScope::install(self.text_measurer.as_deref_mut());
         unsafe { external::Clay_BeginLayout() };
-        let builder = &ui::Builder::new();
+        let mut press_state = std::mem::take(&mut self.press_state);
+        press_state.update(&unsafe { external::ClayRs_GetPointerData() });
+        let mut text_storage = std::mem::take(&mut self.text_storage);
+        text_storage.next_frame();
+        let mut custom_data = std::mem::take(&mut self.custom_data);
+        custom_data.clear();
+        let key_events = std::mem::take(&mut self.key_events);
+        let mut focus_state = std::mem::take(&mut self.focus_state);
+        focus_state.traverse(&key_events);
+        let text_inputs = std::mem::take(&mut self.text_inputs);
+        let clipboard = self.clipboard.take();
+        let frame = FrameGuard {
+            frame: FrameStorage::new(
+                press_state,
+                text_storage,
+                custom_data,
+                focus_state,
+                key_events,
+                text_inputs,
+                clipboard,
+            ),
+            arena: self,
+        };
+        let builder = &ui::Builder::new(&frame.frame);
         ui(builder);
-        self.render_commands = unsafe { external::Clay_EndLayout() }.into_iter();
+        frame.arena.render_commands = unsafe { external::Clay_EndLayout() }.into_iter();
+        frame.frame.dispatch_pointer_handlers();
+    }
+
+    // Returns the errors logged since the last render if the ErrorHandler collects them
+    pub fn render<'f, F>(&mut self, renderer: &impl Renderer, ui: F) -> Vec<OwnedClayError>
+    where
+        F: FnOnce(&ui::Builder<'f>),
+    {
+        let _context = self.enter();
+        Arena::set_layout_dimensions(renderer.prepare_frame());
+        self.key_events.extend(renderer.key_events());
+        self.layout_frame(ui);
         renderer.render(&mut self.render_commands);
+        self.take_errors()
+    }
+
+    // Lays out a frame without a Renderer, copying the render commands out of the arena
+    pub fn layout<'f, F>(&mut self, dimensions: data::Dimensions, ui: F) -> Vec<OwnedRenderCommand>
+    where
+        F: FnOnce(&ui::Builder<'f>),
+    {
+        self.compute_layout(dimensions, ui);
+        self.render_commands
+            .by_ref()
+            .filter_map(|command| OwnedRenderCommand::try_from(command).ok())
+            .collect()
+    }
+
+    // Lays out a frame without a Renderer, for querying element bounds
+    pub fn compute_layout<'f, F>(
+        &mut self,
+        dimensions: data::Dimensions,
+        ui: F,
+    ) -> LayoutResult<'_, 'a>
+    where
+        F: FnOnce(&ui::Builder<'f>),
+    {
+        {
+            let _context = self.enter();
+            Arena::set_layout_dimensions(dimensions);
+            self.layout_frame(ui);
+        }
+        LayoutResult { arena: self }
+    }
+
+    // The errors logged since they were last taken if the ErrorHandler collects them
+    pub fn take_errors(&mut self) -> Vec<OwnedClayError> {
+        std::mem::take(&mut unsafe { self.error_state.as_mut() }.errors)
+    }
+}
+
+// The arena stays borrowed so the layout can't change while it is queried
+#[derive(Debug)]
+pub struct LayoutResult<'r, 'a> {
+    arena: &'r Arena<'a>,
+}
+
+impl LayoutResult<'_, '_> {
+    pub fn bounds(&self, id: ui::ElementId) -> Option<data::BoundingBox> {
+        let _context = self.arena.enter();
+        let mut found = false;
+        let bounding_box = unsafe { external::ClayRs_GetElementBoundingBox(id, &mut found) };
+        found.then_some(bounding_box)
+    }
+}
+
+impl Drop for Arena<'_> {
+    fn drop(&mut self) {
+        if self.initialized {
+            let state = self.context_state_ptr();
+            if let Some(live) = LIVE_CONTEXTS.lock().unwrap().as_mut() {
+                live.remove(&(state as usize));
+            }
+            // The globals point into this arena's memory
+            if CURRENT_CONTEXT
+                .compare_exchange(
+                    state,
+                    std::ptr::null_mut(),
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                )
+                .is_ok()
+            {
+                unsafe { external::ClayRs_ResetContextState() };
+            }
+        }
+        drop(unsafe { Box::from_raw(self.context_state.as_ptr()) });
+        drop(unsafe { Box::from_raw(self.error_state.as_ptr()) });
+        if let ArenaMemory::Owned(layout) = self.memory {
+            unsafe { alloc::dealloc(self.internal.memory as *mut u8, layout) };
+        }
+    }
+}
+
+// Moves the frame's state back into the arena, also when the ui closure panics
+struct FrameGuard<'s, 'a, 'f> {
+    arena: &'s mut Arena<'a>,
+    frame: FrameStorage<'f>,
+}
+
+impl Drop for FrameGuard<'_, '_, '_> {
+    fn drop(&mut self) {
+        let frame = std::mem::take(&mut self.frame);
+        let arena = &mut *self.arena;
+        arena.focus_state = frame.finish_focus();
+        arena.press_state = frame.press_state.into_inner();
+        arena.text_storage = frame.text_storage.into_inner();
+        arena.custom_data = frame.custom_data.into_inner();
+        arena.text_inputs = frame.text_inputs.into_inner();
+        arena.clipboard = frame.clipboard.into_inner();
+    }
+}
+
+// An independent clay context, calls are scoped to its arena
+#[derive(Debug)]
+pub struct Context<'a> {
+    arena: Arena<'a>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(arena: Arena<'a>) -> Self {
+        Self { arena }
+    }
+    pub fn with_capacity(layout_dimensions: data::Dimensions, options: ArenaOptions<'a>) -> Self {
+        Self::new(Arena::with_capacity(layout_dimensions, options))
+    }
+    pub fn arena(&mut self) -> &mut Arena<'a> {
+        &mut self.arena
+    }
+    // Makes this the context used by unscoped calls such as ElementId::find
+    pub fn make_current(&self) {
+        self.arena.make_current();
+    }
+    pub fn render<'f, F>(&mut self, renderer: &impl Renderer, ui: F) -> Vec<OwnedClayError>
+    where
+        F: FnOnce(&ui::Builder<'f>),
+    {
+        self.arena.render(renderer, ui)
+    }
+    pub fn layout<'f, F>(&mut self, dimensions: data::Dimensions, ui: F) -> Vec<OwnedRenderCommand>
+    where
+        F: FnOnce(&ui::Builder<'f>),
+    {
+        self.arena.layout(dimensions, ui)
+    }
+    pub fn compute_layout<'f, F>(
+        &mut self,
+        dimensions: data::Dimensions,
+        ui: F,
+    ) -> LayoutResult<'_, 'a>
+    where
+        F: FnOnce(&ui::Builder<'f>),
+    {
+        self.arena.compute_layout(dimensions, ui)
+    }
+    // clay: Clay_SetDebugModeEnabled
+    pub fn set_debug_mode(&self, enabled: bool) {
+        let _context = self.arena.enter();
+        unsafe { external::Clay_SetDebugModeEnabled(enabled) };
+    }
+    // clay: Clay_GetElementId
+    pub fn find<'s>(&self, id: data::String<'s>) -> ui::ElementId<'s> {
+        let _context = self.arena.enter();
+        ui::ElementId::find(id)
+    }
+    // clay: Clay_PointerOver
+    pub fn is_pointer_over(&self, id: &ui::ElementId) -> bool {
+        let _context = self.arena.enter();
+        id.is_pointer_over()
+    }
+    // clay: Clay_GetScrollContainerData
+    pub fn get_scroll_container_data(&self, id: &ui::ElementId) -> data::ScrollContainerData<'_> {
+        let _context = self.arena.enter();
+        unsafe { external::Clay_GetScrollContainerData(*id) }
+    }
+    // clay: Clay_SetPointerState
+    pub fn set_pointer_state(&self, position: data::Vector2, pointer_down: bool) {
+        let _context = self.arena.enter();
+        ui::Builder::set_pointer_state(position, pointer_down);
+    }
+    pub fn set_key_events(&mut self, key_events: &[data::KeyEvent]) {
+        self.arena.set_key_events(key_events);
+    }
+    // clay: Clay_UpdateScrollContainers
+    pub fn update_scroll_containers(
+        &self,
+        enable_drag_scrolling: bool,
+        scroll_delta: data::Vector2,
+        delta_time: f32,
+    ) {
+        let _context = self.arena.enter();
+        ui::Builder::update_scroll_containers(enable_drag_scrolling, scroll_delta, delta_time);
     }
 }
 
 #[packed_enum]
 #[derive(Copy, Clone)]
+#[allow(dead_code)]
 // clay: Clay__ElementConfigType
 pub(crate) enum ElementConfigType {
     Rectangle = 1,
@@ -167,6 +1102,7 @@ pub(crate) union ElementConfigUnion<'a> {
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
+#[allow(dead_code)]
 // clay: Clay_RenderCommandType
 enum RenderCommandType {
     None,
@@ -224,6 +1160,163 @@ impl RenderCommand<'_> {
             RenderCommandType::ScissorEnd => RenderCommandElement::ScissorEnd,
         }
     }
+    pub fn rectangle_config(&self) -> Option<&ui::Rectangle> {
+        match self.command_type {
+            RenderCommandType::Rectangle => Some(unsafe { self.config.rectangle_element_config }),
+            _ => None,
+        }
+    }
+    pub fn border_config(&self) -> Option<&ui::Border> {
+        match self.command_type {
+            RenderCommandType::Border => Some(unsafe { self.config.border_element_config }),
+            _ => None,
+        }
+    }
+    pub fn text_config(&self) -> Option<&ui::Text> {
+        match self.command_type {
+            RenderCommandType::Text => Some(unsafe { self.config.text_element_config }),
+            _ => None,
+        }
+    }
+    pub fn image_config(&self) -> Option<&ui::Image> {
+        match self.command_type {
+            RenderCommandType::Image => Some(unsafe { self.config.image_element_config }),
+            _ => None,
+        }
+    }
+    pub fn custom_config(&self) -> Option<&ui::Custom> {
+        match self.command_type {
+            RenderCommandType::Custom => Some(unsafe { self.config.custom_element_config }),
+            _ => None,
+        }
+    }
+    // The payload of a ui::Custom::new element if it is a T
+    pub fn custom<T: 'static>(&self) -> Option<&T> {
+        self.custom_payload()?.downcast_ref()
+    }
+    fn custom_payload(&self) -> Option<&CustomPayload> {
+        let data = self.custom_config()?.data()?;
+        // Custom configs are only created by Custom::new, from FrameStorage::store_custom
+        Some(unsafe { &*(data as *const CustomPayload) })
+    }
+}
+
+// A RenderCommand copied out of the arena so it can outlive the frame
+#[derive(Debug, Clone)]
+pub enum OwnedRenderCommand {
+    Rectangle {
+        id: u32,
+        bounding_box: data::BoundingBox,
+        config: ui::Rectangle,
+    },
+    Border {
+        id: u32,
+        bounding_box: data::BoundingBox,
+        config: ui::Border,
+    },
+    Text {
+        id: u32,
+        bounding_box: data::BoundingBox,
+        text: String,
+        config: ui::Text,
+    },
+    Image {
+        id: u32,
+        bounding_box: data::BoundingBox,
+        config: ui::Image,
+    },
+    ScissorStart {
+        id: u32,
+        bounding_box: data::BoundingBox,
+    },
+    ScissorEnd {
+        id: u32,
+        bounding_box: data::BoundingBox,
+    },
+    Custom {
+        id: u32,
+        bounding_box: data::BoundingBox,
+        data: Option<Arc<dyn Any + Send + Sync>>,
+    },
+}
+
+// Owned commands can be handed to another thread to be drawn
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<OwnedRenderCommand>();
+};
+
+impl OwnedRenderCommand {
+    pub fn id(&self) -> u32 {
+        match self {
+            Self::Rectangle { id, .. }
+            | Self::Border { id, .. }
+            | Self::Text { id, .. }
+            | Self::Image { id, .. }
+            | Self::ScissorStart { id, .. }
+            | Self::ScissorEnd { id, .. }
+            | Self::Custom { id, .. } => *id,
+        }
+    }
+    pub fn bounding_box(&self) -> data::BoundingBox {
+        match self {
+            Self::Rectangle { bounding_box, .. }
+            | Self::Border { bounding_box, .. }
+            | Self::Text { bounding_box, .. }
+            | Self::Image { bounding_box, .. }
+            | Self::ScissorStart { bounding_box, .. }
+            | Self::ScissorEnd { bounding_box, .. }
+            | Self::Custom { bounding_box, .. } => *bounding_box,
+        }
+    }
+    // The payload of a ui::Custom::new element if it is a T, kept alive by the command
+    pub fn custom<T: 'static>(&self) -> Option<&T> {
+        match self {
+            Self::Custom { data, .. } => data.as_ref()?.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<&RenderCommand<'_>> for OwnedRenderCommand {
+    type Error = ();
+
+    // Fails for RenderCommandType::None, which renderers skip
+    fn try_from(command: &RenderCommand<'_>) -> Result<Self, Self::Error> {
+        let id = command.id;
+        let bounding_box = command.bounding_box;
+        Ok(match command.element() {
+            RenderCommandElement::None => return Err(()),
+            RenderCommandElement::Rectangle(config) => Self::Rectangle {
+                id,
+                bounding_box,
+                config,
+            },
+            RenderCommandElement::Border(config) => Self::Border {
+                id,
+                bounding_box,
+                config,
+            },
+            RenderCommandElement::Text(config) => Self::Text {
+                id,
+                bounding_box,
+                text: <&str>::from(command.text).to_owned(),
+                config,
+            },
+            RenderCommandElement::Image(config) => Self::Image {
+                id,
+                bounding_box,
+                config,
+            },
+            RenderCommandElement::ScissorStart => Self::ScissorStart { id, bounding_box },
+            RenderCommandElement::ScissorEnd => Self::ScissorEnd { id, bounding_box },
+            RenderCommandElement::Custom(_) => Self::Custom {
+                id,
+                bounding_box,
+                data: command.custom_payload().cloned(),
+            },
+        })
+    }
 }
 
 impl fmt::Debug for RenderCommand<'_> {
@@ -314,21 +1407,34 @@ impl<'a> IntoIterator for RenderCommandArray<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+
+    // Clay state is global, tests must not initialize concurrently
+    static CLAY: Mutex<()> = Mutex::new(());
 
     #[test]
     fn new_arena() {
-        let memory = [0u8; 1024];
-        let arena = Arena::new(&memory);
-        assert_eq!(arena.internal.capacity, memory.len() as u64);
+        let mut memory = [0u8; 1024];
+        let arena = Arena::new(&mut memory);
+        assert_eq!(arena.internal.capacity, 1024);
     }
 
     #[test]
     fn initialize_arena() {
+        let _clay = CLAY.lock().unwrap();
         let size: u32 = Arena::min_memory_size();
-        let memory = vec![0u8; size as usize];
-        let arena = Arena::new(&memory);
-        assert_eq!(arena.internal.capacity, memory.len() as u64);
+        let mut memory = vec![0u8; size as usize];
+        let mut arena = Arena::new(&mut memory);
+        assert_eq!(arena.internal.capacity, size as u64);
         let dimensions = data::Dimensions::new(300.0, 300.0);
         arena.initialize(dimensions, data::default());
     }
+
+    #[test]
+    fn arena_with_capacity() {
+        let _clay = CLAY.lock().unwrap();
+        let arena = Arena::with_capacity(data::Dimensions::new(300.0, 300.0), data::default());
+        assert_eq!(arena.internal.capacity, Arena::min_memory_size() as u64);
+        assert_eq!(arena.internal.memory as usize % ARENA_ALIGNMENT, 0);
+    }
 }
@@ -0,0 +1,213 @@
+// Snapshot testing of layouts, enabled by the "testing" feature
+use crate::{data, external, system, ui};
+use std::{cell::RefCell, fmt::Write, fs, path::PathBuf};
+
+// Set to update snapshot files instead of comparing against them
+pub const UPDATE_SNAPSHOTS_ENV: &str = "CLAY_UPDATE_SNAPSHOTS";
+
+// Deterministic text measurement, each char is font_size high and half as wide
+pub fn measure_text(text: &str, config: &ui::Text) -> data::Dimensions {
+    let font_size = config.font_size as f32;
+    data::Dimensions {
+        width: text.chars().count() as f32 * font_size / 2.,
+        height: if config.line_height > 0 {
+            config.line_height as f32
+        } else {
+            font_size
+        },
+    }
+}
+
+// Captures each frame as one line per render command, hash ids are replaced
+// by their string ids or omitted and floats rounded to 2 decimals
+pub struct SnapshotRenderer {
+    dimensions: data::Dimensions,
+    snapshot: RefCell<String>,
+}
+
+impl SnapshotRenderer {
+    pub fn new(dimensions: data::Dimensions) -> Self {
+        Self {
+            dimensions,
+            snapshot: RefCell::new(String::new()),
+        }
+    }
+
+    // The last rendered frame
+    pub fn snapshot(&self) -> String {
+        self.snapshot.borrow().clone()
+    }
+
+    // Compares the last rendered frame with tests/snapshots/<name>.snap
+    pub fn assert_snapshot(&self, name: &str) {
+        assert_snapshot(name, &self.snapshot.borrow());
+    }
+}
+
+impl system::Renderer for SnapshotRenderer {
+    fn prepare_frame(&self) -> data::Dimensions {
+        self.dimensions
+    }
+
+    fn render(&self, render_commands: &mut system::RenderCommandIter<'_>) {
+        let mut snapshot = String::new();
+        let mut depth = 0;
+        for command in render_commands {
+            if let system::RenderCommandElement::ScissorEnd = command.element() {
+                depth = usize::max(depth, 1) - 1;
+            }
+            snapshot.push_str(&"  ".repeat(depth));
+            // Writing to a String can't fail
+            write_command(&mut snapshot, command).unwrap();
+            snapshot.push('\n');
+            if let system::RenderCommandElement::ScissorStart = command.element() {
+                depth += 1;
+            }
+        }
+        *self.snapshot.borrow_mut() = snapshot;
+    }
+}
+
+// Compares actual with tests/snapshots/<name>.snap in the crate being tested,
+// writing it instead if CLAY_UPDATE_SNAPSHOTS is set
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let directory = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default())
+        .join("tests")
+        .join("snapshots");
+    let path = directory.join(format!("{name}.snap"));
+    if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    match fs::read_to_string(&path) {
+        Ok(expected) => assert_eq!(
+            expected,
+            actual,
+            "snapshot {} differs, set {UPDATE_SNAPSHOTS_ENV}=1 to update it",
+            path.display()
+        ),
+        Err(error) => panic!(
+            "can't read snapshot {} ({error}), set {UPDATE_SNAPSHOTS_ENV}=1 to create it",
+            path.display()
+        ),
+    }
+}
+
+struct Number(f32);
+
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rounded = format!("{:.2}", self.0);
+        let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+        f.write_str(if trimmed == "-0" { "0" } else { trimmed })
+    }
+}
+
+struct Color(data::Color);
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let data::Color { r, g, b, a } = self.0;
+        write!(
+            f,
+            "rgba({}, {}, {}, {})",
+            Number(r),
+            Number(g),
+            Number(b),
+            Number(a)
+        )
+    }
+}
+
+struct CornerRadius(data::CornerRadius);
+
+impl std::fmt::Display for CornerRadius {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let radius = self.0;
+        write!(
+            f,
+            "radius=({}, {}, {}, {})",
+            Number(radius.top_left),
+            Number(radius.top_right),
+            Number(radius.bottom_left),
+            Number(radius.bottom_right)
+        )
+    }
+}
+
+fn write_command(out: &mut String, command: &system::RenderCommand) -> std::fmt::Result {
+    let element = command.element();
+    let name = match element {
+        system::RenderCommandElement::None => "None",
+        system::RenderCommandElement::Rectangle(_) => "Rectangle",
+        system::RenderCommandElement::Border(_) => "Border",
+        system::RenderCommandElement::Text(_) => "Text",
+        system::RenderCommandElement::Image(_) => "Image",
+        system::RenderCommandElement::ScissorStart => "ScissorStart",
+        system::RenderCommandElement::ScissorEnd => "ScissorEnd",
+        system::RenderCommandElement::Custom(_) => "Custom",
+    };
+    write!(out, "{name}")?;
+    let id = unsafe { external::ClayRs_GetElementIdForHash(command.id) };
+    let string_id: &str = id.string_id().into();
+    if !string_id.is_empty() {
+        write!(out, " {string_id}")?;
+        if id.offset() != 0 {
+            write!(out, "[{}]", id.offset())?;
+        }
+    }
+    let bounding_box = command.bounding_box;
+    write!(
+        out,
+        " ({}, {}, {}x{})",
+        Number(bounding_box.x),
+        Number(bounding_box.y),
+        Number(bounding_box.width),
+        Number(bounding_box.height)
+    )?;
+    match element {
+        system::RenderCommandElement::Rectangle(rectangle) => {
+            write!(out, " {}", Color(rectangle.color))?;
+            if rectangle.corner_radius != data::CornerRadius::default() {
+                write!(out, " {}", CornerRadius(rectangle.corner_radius))?;
+            }
+        }
+        system::RenderCommandElement::Border(border) => {
+            for (side, style) in [
+                ("left", border.left),
+                ("right", border.right),
+                ("top", border.top),
+                ("bottom", border.bottom),
+                ("between", border.between_children),
+            ] {
+                if style.width > 0 {
+                    write!(out, " {side}={} {}", style.width, Color(style.color))?;
+                }
+            }
+            if border.corner_radius != data::CornerRadius::default() {
+                write!(out, " {}", CornerRadius(border.corner_radius))?;
+            }
+        }
+        system::RenderCommandElement::Text(text) => {
+            let string: &str = command.text.into();
+            write!(
+                out,
+                " {string:?} font={} size={} {}",
+                text.font_id,
+                text.font_size,
+                Color(text.text_color)
+            )?;
+        }
+        system::RenderCommandElement::Image(image) => {
+            write!(
+                out,
+                " source={}x{}",
+                Number(image.source_dimensions.width),
+                Number(image.source_dimensions.height)
+            )?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
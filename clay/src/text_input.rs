@@ -0,0 +1,523 @@
+use crate::ui::Element;
+use crate::{data, external, system, ui};
+use std::collections::HashMap;
+
+// Cursor and selection of each TextInput, kept by the Arena across frames
+pub(crate) type TextInputStates = HashMap<u32, TextInputState>;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub(crate) struct TextInputState {
+    // Byte offsets into the text, the selection is between anchor and cursor
+    cursor: usize,
+    anchor: usize,
+}
+
+impl TextInputState {
+    fn selection(&self) -> (usize, usize) {
+        (self.cursor.min(self.anchor), self.cursor.max(self.anchor))
+    }
+
+    fn has_selection(&self) -> bool {
+        self.cursor != self.anchor
+    }
+
+    // The application may have changed the text since the last frame
+    fn clamp(&mut self, text: &str) {
+        let clamp = |mut index: usize| {
+            index = index.min(text.len());
+            while !text.is_char_boundary(index) {
+                index -= 1;
+            }
+            index
+        };
+        self.cursor = clamp(self.cursor);
+        self.anchor = clamp(self.anchor);
+    }
+
+    fn move_to(&mut self, index: usize, extend_selection: bool) {
+        self.cursor = index;
+        if !extend_selection {
+            self.anchor = index;
+        }
+    }
+
+    fn replace_selection(&mut self, text: &mut std::string::String, replacement: &str) {
+        let (start, end) = self.selection();
+        text.replace_range(start..end, replacement);
+        self.move_to(start + replacement.len(), false);
+    }
+
+    fn handle_key_event(
+        &mut self,
+        text: &mut std::string::String,
+        key_event: data::KeyEvent,
+        layout: &TextLayout,
+        clipboard: &mut dyn system::Clipboard,
+        response: &mut TextInputResponse,
+    ) {
+        let (key, modifiers) = match key_event {
+            data::KeyEvent::Text(c) => {
+                if !c.is_control() {
+                    self.replace_selection(text, c.encode_utf8(&mut [0; 4]));
+                    response.changed = true;
+                }
+                return;
+            }
+            data::KeyEvent::Key { key, modifiers } => (key, modifiers),
+        };
+        // Alt moves by word on macOS, Command is the shortcut modifier
+        let by_word = modifiers.control || modifiers.alt;
+        let shortcut = modifiers.control || modifiers.logo;
+        let extend_selection = modifiers.shift;
+        match key {
+            data::Key::Left => {
+                let index = match (self.has_selection() && !extend_selection, by_word) {
+                    (true, _) => self.selection().0,
+                    (false, true) => previous_word(text, self.cursor),
+                    (false, false) => previous_char(text, self.cursor),
+                };
+                self.move_to(index, extend_selection);
+            }
+            data::Key::Right => {
+                let index = match (self.has_selection() && !extend_selection, by_word) {
+                    (true, _) => self.selection().1,
+                    (false, true) => next_word(text, self.cursor),
+                    (false, false) => next_char(text, self.cursor),
+                };
+                self.move_to(index, extend_selection);
+            }
+            data::Key::Home | data::Key::End if shortcut => {
+                let index = if key == data::Key::Home {
+                    0
+                } else {
+                    text.len()
+                };
+                self.move_to(index, extend_selection);
+            }
+            data::Key::Home | data::Key::End | data::Key::Up | data::Key::Down => {
+                let lines = layout.lines(text);
+                let line = line_at(&lines, self.cursor);
+                let index = match key {
+                    data::Key::Home => lines[line].start,
+                    data::Key::End => lines[line].end,
+                    data::Key::Up if line == 0 => 0,
+                    data::Key::Down if line + 1 == lines.len() => text.len(),
+                    _ => {
+                        let target = if key == data::Key::Up {
+                            line - 1
+                        } else {
+                            line + 1
+                        };
+                        let x = layout.x(text, lines[line], self.cursor);
+                        layout.index_at(text, lines[target], x)
+                    }
+                };
+                self.move_to(index, extend_selection);
+            }
+            data::Key::Backspace | data::Key::Delete => {
+                if !self.has_selection() {
+                    self.anchor = match (key, by_word) {
+                        (data::Key::Backspace, true) => previous_word(text, self.cursor),
+                        (data::Key::Backspace, false) => previous_char(text, self.cursor),
+                        (_, true) => next_word(text, self.cursor),
+                        (_, false) => next_char(text, self.cursor),
+                    };
+                }
+                if self.has_selection() {
+                    self.replace_selection(text, "");
+                    response.changed = true;
+                }
+            }
+            data::Key::Enter if layout.multiline => {
+                self.replace_selection(text, "\n");
+                response.changed = true;
+            }
+            data::Key::Enter => response.submitted = true,
+            data::Key::Character('a') if shortcut => {
+                self.anchor = 0;
+                self.cursor = text.len();
+            }
+            data::Key::Character(c @ ('c' | 'x')) if shortcut && self.has_selection() => {
+                let (start, end) = self.selection();
+                clipboard.set_text(&text[start..end]);
+                if c == 'x' {
+                    self.replace_selection(text, "");
+                    response.changed = true;
+                }
+            }
+            data::Key::Character('v') if shortcut => {
+                if let Some(pasted) = clipboard.get_text() {
+                    // A single line input gets the lines joined with spaces
+                    let pasted: std::string::String = pasted
+                        .replace("\r\n", "\n")
+                        .chars()
+                        .filter_map(|c| match c {
+                            '\n' if !layout.multiline => Some(' '),
+                            '\n' => Some('\n'),
+                            c if c.is_control() => None,
+                            c => Some(c),
+                        })
+                        .collect();
+                    self.replace_selection(text, &pasted);
+                    response.changed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn previous_char(text: &str, index: usize) -> usize {
+    text[..index]
+        .char_indices()
+        .next_back()
+        .map_or(0, |(i, _)| i)
+}
+
+fn next_char(text: &str, index: usize) -> usize {
+    text[index..]
+        .chars()
+        .next()
+        .map_or(index, |c| index + c.len_utf8())
+}
+
+// Start of the word before the index, skipping punctuation and spaces
+fn previous_word(text: &str, index: usize) -> usize {
+    let mut start = index;
+    let mut in_word = false;
+    for (i, c) in text[..index].char_indices().rev() {
+        if is_word_char(c) {
+            in_word = true;
+        } else if in_word {
+            break;
+        }
+        start = i;
+    }
+    start
+}
+
+// End of the word after the index, skipping punctuation and spaces
+fn next_word(text: &str, index: usize) -> usize {
+    let mut in_word = false;
+    for (i, c) in text[index..].char_indices() {
+        if is_word_char(c) {
+            in_word = true;
+        } else if in_word {
+            return index + i;
+        }
+    }
+    text.len()
+}
+
+// A line as displayed, without the newline or the spaces it was wrapped at
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Line {
+    start: usize,
+    end: usize,
+}
+
+// The line the index is displayed on, an index where a line wraps is on the next line
+fn line_at(lines: &[Line], index: usize) -> usize {
+    lines
+        .iter()
+        .rposition(|line| line.start <= index)
+        .unwrap_or(0)
+}
+
+struct TextLayout<'m> {
+    measure: &'m dyn Fn(&str) -> f32,
+    multiline: bool,
+    // Width the text wraps at, the text area's width in the last layout
+    wrap_width: Option<f32>,
+}
+
+impl TextLayout<'_> {
+    fn lines(&self, text: &str) -> Vec<Line> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for paragraph in text.split('\n') {
+            let end = start + paragraph.len();
+            match self.wrap_width {
+                Some(width) => self.wrap(text, Line { start, end }, width, &mut lines),
+                None => lines.push(Line { start, end }),
+            }
+            start = end + 1;
+        }
+        lines
+    }
+
+    // Breaks before the first word that overflows, like clay a word wider
+    // than the text area gets a line of its own
+    fn wrap(&self, text: &str, paragraph: Line, width: f32, lines: &mut Vec<Line>) {
+        let mut line = Line {
+            start: paragraph.start,
+            end: paragraph.start,
+        };
+        let mut index = paragraph.start;
+        while index < paragraph.end {
+            let word_start = text[index..paragraph.end]
+                .find(|c| c != ' ')
+                .map_or(paragraph.end, |i| index + i);
+            if word_start == paragraph.end {
+                break;
+            }
+            let word_end = text[word_start..paragraph.end]
+                .find(' ')
+                .map_or(paragraph.end, |i| word_start + i);
+            if line.end > line.start && (self.measure)(&text[line.start..word_end]) > width {
+                lines.push(line);
+                line.start = word_start;
+            }
+            line.end = word_end;
+            index = word_end;
+        }
+        line.end = paragraph.end;
+        lines.push(line);
+    }
+
+    // Offset of the index from the start of the line
+    fn x(&self, text: &str, line: Line, index: usize) -> f32 {
+        (self.measure)(&text[line.start..index.clamp(line.start, line.end)])
+    }
+
+    // The char boundary in the line closest to the offset
+    fn index_at(&self, text: &str, line: Line, x: f32) -> usize {
+        let (mut closest, mut distance) = (line.start, x.abs());
+        for (i, c) in text[line.start..line.end].char_indices() {
+            let index = line.start + i + c.len_utf8();
+            let width = (self.measure)(&text[line.start..index]);
+            if (width - x).abs() < distance {
+                (closest, distance) = (index, (width - x).abs());
+            }
+            if width > x {
+                break;
+            }
+        }
+        closest
+    }
+}
+
+// clay: CLAY_IDI_LOCAL
+fn local_id(string_id: &'static str, offset: usize, parent_id: u32) -> ui::ElementId<'static> {
+    unsafe { external::Clay__HashString(string_id.into(), offset as u32, parent_id) }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct TextInputResponse {
+    // The text was edited this frame
+    pub changed: bool,
+    // Enter was pressed in a single line input
+    pub submitted: bool,
+    pub focused: bool,
+    pub interaction: ui::Interaction,
+}
+
+// Edits text owned by the application, the cursor and selection are kept per element id.
+// The text is laid out as one row of Text elements per line, with the selection
+// highlighted by a Rectangle around the selected part
+#[derive(Debug)]
+pub struct TextInput<'t> {
+    text: &'t mut std::string::String,
+    config: ui::Text,
+    multiline: bool,
+    cursor_color: Option<data::Color>,
+    selection_color: data::Color,
+}
+
+impl<'t> TextInput<'t> {
+    pub fn new(text: &'t mut std::string::String) -> Self {
+        Self {
+            text,
+            config: ui::Text::default(),
+            multiline: false,
+            cursor_color: None,
+            selection_color: data::Color::rgba(0., 120., 215., 96.),
+        }
+    }
+    // Words wrap at the input's width in multiline inputs, newlines always break a line
+    pub fn text_config(self, config: ui::Text) -> Self {
+        Self { config, ..self }
+    }
+    pub fn multiline(self, multiline: bool) -> Self {
+        Self { multiline, ..self }
+    }
+    // The text color by default
+    pub fn cursor_color(self, color: data::Color) -> Self {
+        Self {
+            cursor_color: Some(color),
+            ..self
+        }
+    }
+    pub fn selection_color(self, color: data::Color) -> Self {
+        Self {
+            selection_color: color,
+            ..self
+        }
+    }
+
+    // Declares the input as an element configured by items, which should attach an ElementId
+    // so the input keeps its state when other elements are added before it
+    pub fn build<F>(self, builder: &ui::Builder, items: F) -> TextInputResponse
+    where
+        F: FnOnce(&ui::Builder),
+    {
+        // clay: CLAY macro, the children need the text edited while configuring
+        unsafe { external::Clay__OpenElement() };
+        items(builder);
+        builder.set_focusable();
+        let id = unsafe { external::ClayRs_GetOpenElementId() }.id();
+        let mut response = TextInputResponse {
+            focused: builder.is_focused(),
+            interaction: builder.interaction(),
+            ..Default::default()
+        };
+
+        let frame = builder.frame();
+        let mut state = frame.take_text_input_state(id);
+        state.clamp(self.text);
+        let measure = |text: &str| builder.measure_text(text, &self.config).width;
+        let content_id = local_id("TextInputContent", 0, id);
+        let layout = TextLayout {
+            measure: &measure,
+            multiline: self.multiline,
+            wrap_width: content_id
+                .previous_bounds()
+                .map(|bounds| bounds.width)
+                .filter(|&width| {
+                    width > 0.
+                        && self.multiline
+                        && self.config.wrap_mode == data::TextWrapMode::ClayTextWrapWords
+                }),
+        };
+
+        if response.interaction.pressed {
+            let pointer_data = ui::Builder::pointer_data();
+            let lines = layout.lines(self.text);
+            let line_bounds = (0..lines.len())
+                .filter_map(|i| Some((i, local_id("TextInputLine", i, id).previous_bounds()?)));
+            let mut closest = None;
+            for (i, bounds) in line_bounds {
+                closest = Some((i, bounds));
+                if pointer_data.position.y < bounds.y + bounds.height {
+                    break;
+                }
+            }
+            if let Some((i, bounds)) = closest {
+                let index =
+                    layout.index_at(self.text, lines[i], pointer_data.position.x - bounds.x);
+                let dragging =
+                    pointer_data.state != data::PointerDataInteractionState::PressedThisFrame;
+                state.move_to(index, dragging);
+            }
+        }
+        if response.focused {
+            let mut clipboard = frame.clipboard();
+            for &key_event in builder.key_events() {
+                state.handle_key_event(
+                    self.text,
+                    key_event,
+                    &layout,
+                    &mut *clipboard,
+                    &mut response,
+                );
+            }
+        }
+        frame.set_text_input_state(id, state);
+        unsafe { external::Clay__ElementPostConfiguration() };
+
+        let lines = layout.lines(self.text);
+        let line_height = if self.config.line_height > 0 {
+            self.config.line_height as f32
+        } else {
+            builder.measure_text(" ", &self.config).height
+        };
+        let (selection_start, selection_end) = if response.focused {
+            state.selection()
+        } else {
+            (0, 0)
+        };
+        builder.build(
+            |builder| {
+                content_id.attach(builder);
+                ui::Layout::new()
+                    .width(data::SizingAxis::grow_unbounded())
+                    .direction(data::LayoutDirection::TopToBottom)
+                    .attach(builder);
+            },
+            |builder| {
+                for (i, &line) in lines.iter().enumerate() {
+                    builder.build(
+                        |builder| {
+                            local_id("TextInputLine", i, id).attach(builder);
+                            ui::Layout::new()
+                                .height(data::SizingAxis::fixed(line_height))
+                                .attach(builder);
+                        },
+                        |builder| {
+                            let start = selection_start.clamp(line.start, line.end);
+                            let end = selection_end.clamp(line.start, line.end);
+                            self.segment(builder, line.start, start);
+                            if start < end {
+                                builder.build(
+                                    |builder| {
+                                        ui::Rectangle {
+                                            color: self.selection_color,
+                                            ..Default::default()
+                                        }
+                                        .attach(builder);
+                                    },
+                                    |builder| self.segment(builder, start, end),
+                                );
+                            }
+                            self.segment(builder, end, line.end);
+                            if response.focused && line_at(&lines, state.cursor) == i {
+                                let x = layout.x(self.text, line, state.cursor);
+                                self.cursor(builder, x, line_height);
+                            }
+                        },
+                    );
+                }
+            },
+        );
+        unsafe { external::Clay__CloseElement() };
+        response
+    }
+
+    fn segment(&self, builder: &ui::Builder, start: usize, end: usize) {
+        if start < end {
+            ui::Text {
+                wrap_mode: data::TextWrapMode::ClayTextWrapNone,
+                ..self.config
+            }
+            .with(builder.store_text(&self.text[start..end]))
+            .attach(builder);
+        }
+    }
+
+    // Floats over the row so it doesn't move the text after it
+    fn cursor(&self, builder: &ui::Builder, x: f32, line_height: f32) {
+        builder.build(
+            |builder| {
+                ui::Layout::new()
+                    .sizing(data::Sizing::fixed(1., line_height))
+                    .attach(builder);
+                ui::Rectangle {
+                    color: self.cursor_color.unwrap_or(self.config.text_color),
+                    ..Default::default()
+                }
+                .attach(builder);
+                ui::Floating {
+                    offset: data::Vector2 { x, y: 0. },
+                    pointer_capture_mode: data::PointerCaptureMode::Passthrough,
+                    ..Default::default()
+                }
+                .attach(builder);
+            },
+            ui::no_children,
+        );
+    }
+}
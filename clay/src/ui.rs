@@ -1,19 +1,33 @@
 use crate::data;
 use crate::external;
-use crate::system::{ElementConfigType, ElementConfigUnion};
+use crate::system::{ElementConfigType, ElementConfigUnion, FrameStorage};
+use std::cell::Cell;
 use std::marker::PhantomData;
+use std::os::raw::c_char;
 use std::os::raw::c_float;
 use std::os::raw::c_void;
+use std::sync::Arc;
 
 pub trait Element {
     fn attach(&self, builder: &crate::ui::Builder);
 }
 
-pub struct Builder<'a>((), PhantomData<&'a ()>);
+pub struct Builder<'a> {
+    // FrameStorage<'a>, erased to keep Builder covariant
+    frame: *const (),
+    _lifetime_marker: PhantomData<&'a ()>,
+}
 
 impl<'a> Builder<'a> {
-    pub(crate) fn new() -> Self {
-        Self((), PhantomData)
+    pub(crate) fn new(frame: &FrameStorage<'a>) -> Self {
+        Self {
+            frame: frame as *const FrameStorage as *const (),
+            _lifetime_marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn frame(&self) -> &FrameStorage<'a> {
+        unsafe { &*(self.frame as *const FrameStorage<'a>) }
     }
 
     // clay: CLAY macro
@@ -33,34 +47,29 @@ impl<'a> Builder<'a> {
         unsafe { external::Clay__CloseElement() };
     }
 
-    // https://adventures.michaelfbryan.com/posts/rust-closures-in-ffi/
-    unsafe extern "C" fn hover_trampoline<F>(
-        element_id: ElementId,
-        pointer_data: data::PointerData,
-        user_data: isize,
-    ) where
-        F: FnMut(ElementId, data::PointerData),
-    {
-        let user_data = &mut *(user_data as *mut F);
-        user_data(element_id, pointer_data);
-    }
-
-    fn get_hover_trampoline<F>(_closure: &F) -> external::OnHoverCallback
+    // clay: Clay_OnHover
+    // Called once layout ends if the pointer is over the element, dropped with the frame
+    pub fn set_on_hover_callback<F>(&self, on_hover: F)
     where
-        F: FnMut(ElementId, data::PointerData),
+        F: FnMut(ElementId, data::PointerData) + 'a,
     {
-        Self::hover_trampoline::<F>
+        let element_id = unsafe { external::ClayRs_GetOpenElementId() };
+        if element_id.id != 0 {
+            self.frame()
+                .add_hover_handler(element_id, Box::new(on_hover));
+        }
     }
 
-    // clay: Clay_OnHover
-    pub fn set_on_hover_callback<F>(&'a self, on_hover: &mut F)
+    // Called once layout ends if the pointer was pressed over the element this frame,
+    // dropped with the frame. interaction() also tracks releases, clicks and drags
+    pub fn set_on_press_callback<F>(&self, on_press: F)
     where
         F: FnMut(ElementId, data::PointerData) + 'a,
     {
-        let mut on_hover = on_hover;
-        let trampoline = Self::get_hover_trampoline(&on_hover);
-        unsafe {
-            external::Clay_OnHover(trampoline, &mut on_hover as *mut _ as isize);
+        let element_id = unsafe { external::ClayRs_GetOpenElementId() };
+        if element_id.id != 0 {
+            self.frame()
+                .add_press_handler(element_id, Box::new(on_press));
         }
     }
 
@@ -81,6 +90,62 @@ impl<'a> Builder<'a> {
         unsafe { external::Clay_Hovered() }
     }
 
+    // The pointer state set for this frame, see set_pointer_state
+    pub fn pointer_data() -> data::PointerData {
+        unsafe { external::ClayRs_GetPointerData() }
+    }
+
+    // Bounds of the open element in the last layout, None on the frame it first appears
+    pub fn previous_bounds(&self) -> Option<data::BoundingBox> {
+        unsafe { external::ClayRs_GetOpenElementId() }.previous_bounds()
+    }
+
+    // Copies text into the arena so it lives until the frame is rendered, see text!
+    pub fn store_text(&self, text: &str) -> data::String<'_> {
+        self.frame().store_text(text)
+    }
+
+    // Pointer interaction with the open element, presses are tracked from the
+    // frame they start on so the element must ask every frame
+    pub fn interaction(&self) -> Interaction {
+        let element_id = unsafe { external::ClayRs_GetOpenElementId() };
+        self.frame()
+            .interaction(element_id.id, Builder::is_hovered())
+    }
+
+    // Adds the open element to Tab traversal, pressing on it focuses it
+    pub fn set_focusable(&self) {
+        let element_id = unsafe { external::ClayRs_GetOpenElementId() };
+        self.frame()
+            .set_focusable(element_id.id, Builder::is_hovered());
+    }
+
+    // While declared, Tab only moves between the focusable elements declared by
+    // children, and focus outside them is cleared
+    pub fn trap_focus<F>(&self, children: F)
+    where
+        F: FnOnce(&Self),
+    {
+        let start = self.frame().focus_order_len();
+        children(self);
+        self.frame().trap_focus(start);
+    }
+
+    pub fn is_focused(&self) -> bool {
+        let element_id = unsafe { external::ClayRs_GetOpenElementId() };
+        self.frame().is_focused(element_id.id)
+    }
+
+    // Measures with the arena's text measurer, clay: Clay__MeasureText
+    pub fn measure_text(&self, text: &str, config: &Text) -> data::Dimensions {
+        unsafe { external::ClayRs_MeasureText(text.into(), config) }
+    }
+
+    // This frame's key events, see Arena::set_key_events
+    pub fn key_events(&self) -> &[data::KeyEvent] {
+        self.frame().key_events()
+    }
+
     // clay: Clay_SetPointerState
     pub fn set_pointer_state(position: data::Vector2, pointer_down: bool) {
         unsafe { external::Clay_SetPointerState(position, pointer_down) };
@@ -103,6 +168,19 @@ impl<'a> Builder<'a> {
 
 pub fn no_children(_: &Builder) {}
 
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct Interaction {
+    pub hovered: bool,
+    // The press started on this element and the pointer is still down
+    pub pressed: bool,
+    // Released over the element the press started on
+    pub clicked: bool,
+    // Released this frame, anywhere, after a press started on this element
+    pub released: bool,
+    // Pointer movement since the press started on this element
+    pub drag_delta: data::Vector2,
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
 // clay: Clay_ElementId
@@ -122,6 +200,43 @@ impl<'a> ElementId<'a> {
     pub fn new_idi(string_id: data::String<'a>, offset: u32) -> Self {
         unsafe { external::Clay__HashString(string_id, offset, 0) }
     }
+    // Same as new_id without calling into clay, usable in const items, see clay_id!
+    pub const fn new_const(string_id: &'a str) -> Self {
+        Self::new_const_idi(string_id, 0)
+    }
+    pub const fn new_const_idi(string_id: &'a str, offset: u32) -> Self {
+        // clay: Clay__HashString
+        let bytes = string_id.as_bytes();
+        let mut base: u32 = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            // C adds a char, which is signed on some platforms
+            base = base.wrapping_add(bytes[i] as c_char as u32);
+            base = base.wrapping_add(base << 10);
+            base ^= base >> 6;
+            i += 1;
+        }
+        let mut hash = base.wrapping_add(offset);
+        hash = hash.wrapping_add(hash << 10);
+        hash ^= hash >> 6;
+
+        hash = hash.wrapping_add(hash << 3);
+        base = base.wrapping_add(base << 3);
+        hash ^= hash >> 11;
+        base ^= base >> 11;
+        hash = hash.wrapping_add(hash << 15);
+        base = base.wrapping_add(base << 15);
+        Self {
+            // Zero is reserved as the null id
+            id: hash.wrapping_add(1),
+            offset,
+            base_id: base.wrapping_add(1),
+            string_id: data::String::new(string_id),
+        }
+    }
+    pub fn id(&self) -> u32 {
+        self.id
+    }
     pub fn offset(&self) -> u32 {
         self.offset
     }
@@ -139,6 +254,31 @@ impl<'a> ElementId<'a> {
     pub fn get_scroll_container_data(&self) -> data::ScrollContainerData<'_> {
         unsafe { external::Clay_GetScrollContainerData(*self) }
     }
+    // Bounds in the last layout, usable while the next one is declared
+    pub fn previous_bounds(&self) -> Option<data::BoundingBox> {
+        let mut found = false;
+        let bounding_box = unsafe { external::ClayRs_GetPreviousBoundingBox(self.id, &mut found) };
+        found.then_some(bounding_box)
+    }
+}
+
+impl ElementId<'static> {
+    // The element clay wraps every layout in, floating elements with its id as
+    // parent_id are placed relative to the whole layout
+    pub const ROOT: Self = Self::new_const("Clay__RootContainer");
+}
+
+// clay_id!("Header") is hashed at compile time, clay_id!("Item", index) at
+// runtime but without calling into clay
+#[macro_export]
+macro_rules! clay_id {
+    ($string_id:literal) => {{
+        const ID: $crate::ElementId<'static> = $crate::ElementId::new_const($string_id);
+        ID
+    }};
+    ($string_id:literal, $offset:expr) => {
+        $crate::ElementId::new_const_idi($string_id, $offset)
+    };
 }
 
 impl Element for ElementId<'_> {
@@ -159,6 +299,44 @@ pub struct Layout {
     pub layout_direction: data::LayoutDirection,
 }
 
+impl Layout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn sizing(self, sizing: data::Sizing) -> Self {
+        Self { sizing, ..self }
+    }
+    pub fn width(mut self, width: data::SizingAxis) -> Self {
+        self.sizing.width = width;
+        self
+    }
+    pub fn height(mut self, height: data::SizingAxis) -> Self {
+        self.sizing.height = height;
+        self
+    }
+    pub fn padding(self, x: u16, y: u16) -> Self {
+        Self {
+            padding: data::Padding::new(x, y),
+            ..self
+        }
+    }
+    pub fn gap(self, child_gap: u16) -> Self {
+        Self { child_gap, ..self }
+    }
+    pub fn align(self, x: data::LayoutAlignmentX, y: data::LayoutAlignmentY) -> Self {
+        Self {
+            child_alignment: data::ChildAlignment { x, y },
+            ..self
+        }
+    }
+    pub fn direction(self, layout_direction: data::LayoutDirection) -> Self {
+        Self {
+            layout_direction,
+            ..self
+        }
+    }
+}
+
 impl Element for Layout {
     fn attach(&self, _builder: &Builder) {
         unsafe { external::Clay__AttachLayoutConfig(external::Clay__StoreLayoutConfig(*self)) };
@@ -203,9 +381,40 @@ pub struct Text {
 }
 
 impl Text {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn font(self, font_id: u16) -> Self {
+        Self { font_id, ..self }
+    }
+    pub fn size(self, font_size: u16) -> Self {
+        Self { font_size, ..self }
+    }
+    pub fn color(self, text_color: data::Color) -> Self {
+        Self { text_color, ..self }
+    }
+    pub fn letter_spacing(self, letter_spacing: u16) -> Self {
+        Self {
+            letter_spacing,
+            ..self
+        }
+    }
+    pub fn line_height(self, line_height: u16) -> Self {
+        Self {
+            line_height,
+            ..self
+        }
+    }
+    pub fn wrap_mode(self, wrap_mode: data::TextWrapMode) -> Self {
+        Self { wrap_mode, ..self }
+    }
     pub fn with<'a>(&self, text: data::String<'a>) -> (data::String<'a>, Text) {
         (text, *self)
     }
+    // The text is copied into the arena when attached
+    pub fn with_owned(&self, text: String) -> (String, Text) {
+        (text, *self)
+    }
 }
 
 impl Element for (data::String<'_>, Text) {
@@ -216,21 +425,40 @@ impl Element for (data::String<'_>, Text) {
     }
 }
 
+impl Element for (String, Text) {
+    fn attach(&self, builder: &Builder) {
+        let text = builder.store_text(&self.0);
+        unsafe {
+            external::Clay__OpenTextElement(text, external::Clay__StoreTextElementConfig(self.1))
+        };
+    }
+}
+
+// Formats text into the arena, config.with(text!(builder, "{} items", count))
+#[macro_export]
+macro_rules! text {
+    ($builder:expr, $($arg:tt)+) => {
+        $builder.store_text(&::std::format!($($arg)+))
+    };
+}
+
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 // clay: Clay_ImageElementConfig
 // clay: CLAY_IMAGE
 pub struct Image {
-    pub image_data: *const c_void, // XXX fix
+    // clay: imageData
+    pub handle: data::ImageHandle,
+    // Clay scales the element's height to this aspect ratio
     pub source_dimensions: data::Dimensions,
     // CLAY_EXTEND_CONFIG_IMAGE
 }
 
-impl Default for Image {
-    fn default() -> Self {
+impl Image {
+    pub fn new(handle: data::ImageHandle, source_dimensions: data::Dimensions) -> Self {
         Self {
-            image_data: std::ptr::null(),
-            source_dimensions: data::Dimensions::default(),
+            handle,
+            source_dimensions,
         }
     }
 }
@@ -283,6 +511,20 @@ pub struct Custom {
     // CLAY_EXTEND_CONFIG_CUSTOM
 }
 
+impl Custom {
+    // The payload is moved into the arena when attached and read back by
+    // renderers with RenderCommand::custom
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<T: Send + Sync + 'static>(data: T) -> CustomElement<T> {
+        CustomElement {
+            data: Cell::new(Some(data)),
+        }
+    }
+    pub(crate) fn data(&self) -> Option<*const c_void> {
+        (!self.custom_data.is_null()).then_some(self.custom_data)
+    }
+}
+
 impl Element for Custom {
     fn attach(&self, _builder: &Builder) {
         unsafe {
@@ -296,6 +538,23 @@ impl Element for Custom {
     }
 }
 
+// A Custom element with its payload, which can only be attached once
+pub struct CustomElement<T> {
+    data: Cell<Option<T>>,
+}
+
+impl<T: Send + Sync + 'static> Element for CustomElement<T> {
+    fn attach(&self, builder: &Builder) {
+        let Some(data) = self.data.take() else {
+            return;
+        };
+        Custom {
+            custom_data: builder.frame().store_custom(Arc::new(data)),
+        }
+        .attach(builder);
+    }
+}
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Default)]
 // clay: Clay_ScrollElementConfig
@@ -1,5 +1,11 @@
 use clay::prelude::*;
+use clay::ImageRegistry;
+use std::cell::RefCell;
 use std::iter::zip;
+use std::sync::Mutex;
+
+// Clay state is global, tests must not lay out concurrently
+static CLAY: Mutex<()> = Mutex::new(());
 
 struct TestRenderer {
     pub expected: Vec<&'static str>,
@@ -22,29 +28,25 @@ impl clay::Renderer for TestRenderer {
 
 #[test]
 fn test_simple_ui() {
-    extern "C" fn measure(text: &clay::String, config: &clay::Text) -> clay::Dimensions {
-        clay::Dimensions {
-            width: (text.len() * 10) as f32,
-            height: config.font_size as f32,
-        }
-    }
-
+    let _clay = CLAY.lock().unwrap();
     let renderer = TestRenderer {
         // Internally generated id's can change, not a good test...
         expected: vec![
-            r#"RenderCommand { bounding_box: BoundingBox { x: 16.0, y: 16.0, width: 32.0, height: 32.0 }, config: Image { image_data: 0x0, source_dimensions: Dimensions { width: 128.0, height: 128.0 } }, text: String { chars: "" }, id: 1782946882, command_type: Image }"#,
+            r#"RenderCommand { bounding_box: BoundingBox { x: 16.0, y: 16.0, width: 32.0, height: 32.0 }, config: Image { handle: ImageHandle(0), source_dimensions: Dimensions { width: 128.0, height: 128.0 } }, text: String { chars: "" }, id: 1782946882, command_type: Image }"#,
             r#"RenderCommand { bounding_box: BoundingBox { x: 64.0, y: 23.0, width: 140.0, height: 18.0 }, config: Text { text_color: Color { r: 240.0, g: 189.0, b: 100.0, a: 255.0 }, font_id: 2, font_size: 18, letter_spacing: 0, line_height: 0, wrap_mode: ClayTextWrapWords }, text: String { chars: "Some text here" }, id: 78651382, command_type: Text }"#,
             r#"RenderCommand { bounding_box: BoundingBox { x: 0.0, y: 0.0, width: 300.0, height: 64.0 }, config: Border { left: BorderStyle { width: 2, color: Color { r: 240.0, g: 189.0, b: 100.0, a: 255.0 } }, right: BorderStyle { width: 2, color: Color { r: 240.0, g: 189.0, b: 100.0, a: 255.0 } }, top: BorderStyle { width: 2, color: Color { r: 240.0, g: 189.0, b: 100.0, a: 255.0 } }, bottom: BorderStyle { width: 2, color: Color { r: 240.0, g: 189.0, b: 100.0, a: 255.0 } }, between_children: BorderStyle { width: 0, color: Color { r: 0.0, g: 0.0, b: 0.0, a: 0.0 } }, corner_radius: CornerRadius { top_left: 10.0, top_right: 10.0, bottom_left: 10.0, bottom_right: 10.0 } }, text: String { chars: "" }, id: 2979443697, command_type: Border }"#,
         ],
     };
 
     let size: u32 = clay::Arena::min_memory_size();
-    let memory = vec![0u8; size as usize];
-    let arena = clay::Arena::new(&memory);
-    clay::Arena::set_measure_text_callback(measure);
+    let mut memory = vec![0u8; size as usize];
+    let mut arena = clay::Arena::new(&mut memory);
+    arena.set_measure_text_callback(|text: &str, config: &clay::Text| clay::Dimensions {
+        width: (text.len() * 10) as f32,
+        height: config.font_size as f32,
+    });
     let dimensions = clay::Dimensions::new(300.0, 300.0);
     arena.initialize(dimensions, clay::default());
-    let mut arena = arena;
     let color = clay::Color {
         r: 240.,
         g: 189.,
@@ -119,3 +121,1071 @@ fn test_simple_ui() {
         )
     });
 }
+
+#[test]
+fn test_duplicate_id_error() {
+    let _clay = CLAY.lock().unwrap();
+    let renderer = TestRenderer { expected: vec![] };
+
+    let mut reported = Vec::new();
+    let mut arena = clay::Arena::with_capacity(
+        clay::Dimensions::new(300.0, 300.0),
+        clay::ArenaOptions {
+            error_handler: clay::ErrorHandler::new(|error: clay::ClayError| {
+                reported.push(error.kind)
+            })
+            .collecting(),
+            ..clay::default()
+        },
+    );
+    let errors = arena.render(&renderer, |builder| {
+        for _ in 0..2 {
+            builder.build(
+                |builder| {
+                    clay::ElementId::new_id(clay::String::from("Duplicate")).attach(builder);
+                },
+                clay::no_children,
+            );
+        }
+    });
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, clay::ErrorType::DuplicateId);
+    drop(arena);
+    assert_eq!(reported, vec![clay::ErrorType::DuplicateId]);
+}
+
+#[test]
+fn test_independent_contexts() {
+    let _clay = CLAY.lock().unwrap();
+    let renderer = TestRenderer { expected: vec![] };
+    let panel = |name: &'static str| {
+        move |builder: &clay::Builder| {
+            builder.build(
+                |builder| {
+                    clay::ElementId::new_id(name.into()).attach(builder);
+                    clay::Layout {
+                        sizing: clay::Sizing {
+                            width: clay::SizingAxis::fixed(100.),
+                            height: clay::SizingAxis::fixed(100.),
+                        },
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                },
+                clay::no_children,
+            )
+        }
+    };
+
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut main = clay::Context::with_capacity(dimensions, clay::default());
+    let mut overlay = clay::Context::with_capacity(dimensions, clay::default());
+    main.render(&renderer, panel("Main"));
+    overlay.render(&renderer, panel("Overlay"));
+
+    let position = clay::Vector2 { x: 50., y: 50. };
+    main.set_pointer_state(position, false);
+    overlay.set_pointer_state(position, false);
+    assert!(main.is_pointer_over(&main.find("Main".into())));
+    assert!(!main.is_pointer_over(&main.find("Overlay".into())));
+    assert!(overlay.is_pointer_over(&overlay.find("Overlay".into())));
+    assert!(!overlay.is_pointer_over(&overlay.find("Main".into())));
+}
+
+#[test]
+fn test_context_defaults() {
+    let _clay = CLAY.lock().unwrap();
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let options = clay::ArenaOptions {
+        max_element_count: Some(256),
+        ..clay::default()
+    };
+    let configured = clay::Context::with_capacity(dimensions, options);
+    let configured_size = clay::Arena::min_memory_size();
+    configured.set_debug_mode(true);
+
+    // Options left unset take clay's defaults rather than the current context's
+    let mut context = clay::Context::with_capacity(dimensions, clay::default());
+    assert!(clay::Arena::min_memory_size() > configured_size);
+    let commands = context.layout(dimensions, |builder| {
+        builder.build(
+            |builder| {
+                clay::Layout::new()
+                    .sizing(clay::Sizing::fixed(10., 10.))
+                    .attach(builder);
+                clay::Rectangle::default().attach(builder);
+            },
+            clay::no_children,
+        )
+    });
+    // No inspector
+    assert_eq!(commands.len(), 1);
+
+    // A frame whose ui panics leaves the context usable
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        context.layout(dimensions, |_| panic!("ui"));
+    }));
+    assert!(panicked.is_err());
+    context
+        .arena()
+        .set_measure_text_callback(clay::testing::measure_text);
+    context
+        .arena()
+        .set_focus(Some(clay::ElementId::new_id("Input".into()).id()));
+    let control = clay::Modifiers {
+        control: true,
+        ..clay::default()
+    };
+    let mut text = String::from("ab");
+    let events = [
+        key(clay::Key::Character('a'), control),
+        key(clay::Key::Character('x'), control),
+        key(clay::Key::Character('v'), control),
+        key(clay::Key::Character('v'), control),
+    ];
+    text_input_frame(
+        &mut context,
+        clay::TextInput::new(&mut text),
+        (250., false),
+        &events,
+    );
+    assert_eq!(text, "abab");
+}
+
+#[test]
+fn test_on_hover_closure() {
+    let _clay = CLAY.lock().unwrap();
+    let renderer = TestRenderer { expected: vec![] };
+    let hovered = std::cell::Cell::new(None);
+    let on_hover = |element_id: clay::ElementId, pointer_data: clay::PointerData| {
+        if pointer_data.state == clay::PointerDataInteractionState::PressedThisFrame {
+            hovered.set(Some(element_id.offset()));
+        }
+    };
+    let ui = |builder: &clay::Builder| {
+        for i in 0..2 {
+            builder.build(
+                |builder| {
+                    clay::ElementId::new_idi("Button".into(), i).attach(builder);
+                    clay::Layout {
+                        sizing: clay::Sizing {
+                            width: clay::SizingAxis::fixed(100.),
+                            height: clay::SizingAxis::fixed(100.),
+                        },
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                    builder.set_on_hover_callback(on_hover);
+                },
+                clay::no_children,
+            )
+        }
+    };
+
+    let mut context =
+        clay::Context::with_capacity(clay::Dimensions::new(300.0, 300.0), clay::default());
+    context.render(&renderer, ui);
+    assert_eq!(hovered.get(), None);
+
+    context.set_pointer_state(clay::Vector2 { x: 150., y: 50. }, true);
+    context.render(&renderer, ui);
+    assert_eq!(hovered.get(), Some(1));
+}
+
+#[test]
+fn test_on_press_closure() {
+    let _clay = CLAY.lock().unwrap();
+    let renderer = TestRenderer { expected: vec![] };
+    let pressed = RefCell::new(Vec::new());
+    let on_press = |element_id: clay::ElementId, _: clay::PointerData| {
+        pressed.borrow_mut().push(element_id.offset());
+    };
+    let ui = |builder: &clay::Builder| {
+        for i in 0..2 {
+            builder.build(
+                |builder| {
+                    clay::ElementId::new_idi("Button".into(), i).attach(builder);
+                    clay::Layout {
+                        sizing: clay::Sizing {
+                            width: clay::SizingAxis::fixed(100.),
+                            height: clay::SizingAxis::fixed(100.),
+                        },
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                    builder.set_on_press_callback(on_press);
+                },
+                clay::no_children,
+            )
+        }
+    };
+
+    let mut context =
+        clay::Context::with_capacity(clay::Dimensions::new(300.0, 300.0), clay::default());
+    context.set_pointer_state(clay::Vector2 { x: 150., y: 50. }, false);
+    context.render(&renderer, ui);
+    assert!(pressed.borrow().is_empty());
+
+    // Only the frame the press starts on
+    context.set_pointer_state(clay::Vector2 { x: 150., y: 50. }, true);
+    context.render(&renderer, ui);
+    context.set_pointer_state(clay::Vector2 { x: 150., y: 50. }, true);
+    context.render(&renderer, ui);
+    assert_eq!(*pressed.borrow(), [1]);
+}
+
+#[test]
+fn test_interaction() {
+    let _clay = CLAY.lock().unwrap();
+    let renderer = TestRenderer { expected: vec![] };
+    let interactions = std::cell::RefCell::new(vec![]);
+    let ui = |builder: &clay::Builder| {
+        for i in 0..2 {
+            builder.build(
+                |builder| {
+                    clay::ElementId::new_idi("Button".into(), i).attach(builder);
+                    clay::Layout {
+                        sizing: clay::Sizing {
+                            width: clay::SizingAxis::fixed(100.),
+                            height: clay::SizingAxis::fixed(100.),
+                        },
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                    interactions.borrow_mut().push(builder.interaction());
+                },
+                clay::no_children,
+            )
+        }
+    };
+    let mut context =
+        clay::Context::with_capacity(clay::Dimensions::new(300.0, 300.0), clay::default());
+    let mut frame = |x: f32, down: bool| {
+        context.set_pointer_state(clay::Vector2 { x, y: 50. }, down);
+        interactions.borrow_mut().clear();
+        context.render(&renderer, ui);
+        interactions.take()
+    };
+
+    frame(50., false);
+    let pressed = frame(50., true);
+    assert!(pressed[0].hovered && pressed[0].pressed);
+    assert!(!pressed[1].pressed);
+    let clicked = frame(60., false);
+    assert!(clicked[0].clicked && clicked[0].released);
+    assert_eq!(clicked[0].drag_delta, clay::Vector2 { x: 10., y: 0. });
+
+    // Press on the first button, release over the second
+    frame(50., true);
+    let dragged = frame(150., true);
+    assert!(dragged[0].pressed && !dragged[0].hovered);
+    assert_eq!(dragged[0].drag_delta, clay::Vector2 { x: 100., y: 0. });
+    let released = frame(150., false);
+    assert!(released[0].released && !released[0].clicked);
+    assert!(released[1].hovered && !released[1].released && !released[1].clicked);
+}
+
+#[test]
+fn test_focus() {
+    let _clay = CLAY.lock().unwrap();
+    let renderer = TestRenderer { expected: vec![] };
+    let focused = RefCell::new(vec![]);
+    let ui = |builder: &clay::Builder| {
+        for i in 0..3 {
+            builder.build(
+                |builder| {
+                    clay::ElementId::new_idi("Field".into(), i).attach(builder);
+                    clay::Layout::new()
+                        .sizing(clay::Sizing::fixed(100., 100.))
+                        .attach(builder);
+                    builder.set_focusable();
+                    if builder.is_focused() {
+                        focused.borrow_mut().push(i);
+                    }
+                },
+                clay::no_children,
+            )
+        }
+    };
+    let mut context =
+        clay::Context::with_capacity(clay::Dimensions::new(400.0, 300.0), clay::default());
+    let mut frame = |x: f32, down: bool, key_events: &[clay::KeyEvent]| {
+        context.set_pointer_state(clay::Vector2 { x, y: 50. }, down);
+        context.set_key_events(key_events);
+        context.render(&renderer, ui);
+        (focused.take(), context.arena().focused())
+    };
+    let tab = |shift| clay::KeyEvent::Key {
+        key: clay::Key::Tab,
+        modifiers: clay::Modifiers {
+            shift,
+            ..clay::default()
+        },
+    };
+
+    assert_eq!(frame(0., false, &[]), (vec![], None));
+    assert_eq!(frame(0., false, &[tab(false)]).0, [0]);
+    assert_eq!(frame(0., false, &[tab(false), tab(false)]).0, [2]);
+    assert_eq!(frame(0., false, &[tab(false)]).0, [0]);
+    assert_eq!(frame(0., false, &[tab(true)]).0, [2]);
+    let (_, id) = frame(0., false, &[]);
+    assert_eq!(id, Some(clay::ElementId::new_idi("Field".into(), 2).id()));
+
+    // Pressing focuses the element under the pointer, pressing elsewhere clears it
+    assert_eq!(frame(150., true, &[]).0, [1]);
+    assert_eq!(frame(150., false, &[]).0, [1]);
+    assert_eq!(frame(50., false, &[]).0, [1]);
+    assert_eq!(frame(350., true, &[]).1, None);
+    assert_eq!(frame(350., false, &[]), (vec![], None));
+
+    // Focus set by the application is dropped once the element isn't declared
+    context
+        .arena()
+        .set_focus(Some(clay::ElementId::new_idi("Field".into(), 0).id()));
+    context.render(&renderer, ui);
+    assert_eq!(focused.take(), [0]);
+    context.render(&renderer, |_| {});
+    assert_eq!(context.arena().focused(), None);
+
+    // Key events go to the context they were set on, not the next one laid out
+    context.render(&renderer, ui);
+    context.set_key_events(&[tab(false)]);
+    let mut other =
+        clay::Context::with_capacity(clay::Dimensions::new(400.0, 300.0), clay::default());
+    other.render(&renderer, ui);
+    assert_eq!(focused.take(), []);
+    context.render(&renderer, ui);
+    assert_eq!(focused.take(), [0]);
+
+    // Renderers deliver the key events from their own input queue
+    struct KeyRenderer(RefCell<Vec<clay::KeyEvent>>);
+
+    impl clay::Renderer for KeyRenderer {
+        fn prepare_frame(&self) -> clay::Dimensions {
+            clay::Dimensions::new(400.0, 300.0)
+        }
+
+        fn render(&self, _: &mut clay::RenderCommandIter<'_>) {}
+
+        fn key_events(&self) -> Vec<clay::KeyEvent> {
+            self.0.take()
+        }
+    }
+
+    let key_renderer = KeyRenderer(RefCell::new(vec![tab(false)]));
+    context.set_key_events(&[tab(false)]);
+    context.render(&key_renderer, ui);
+    assert_eq!(focused.take(), [2]);
+    context.render(&key_renderer, ui);
+    assert_eq!(focused.take(), [2]);
+}
+
+// Lays out a frame with a text input 200 wide at the origin, returning its
+// response and the strings of its Text commands
+fn text_input_frame(
+    context: &mut clay::Context,
+    input: clay::TextInput,
+    pointer: (f32, bool),
+    key_events: &[clay::KeyEvent],
+) -> (clay::TextInputResponse, Vec<String>) {
+    let (x, down) = pointer;
+    context.set_pointer_state(clay::Vector2 { x, y: 5. }, down);
+    context.set_key_events(key_events);
+    let mut response = clay::TextInputResponse::default();
+    let commands = context.layout(clay::Dimensions::new(300., 300.), |builder| {
+        response = input.build(builder, |builder| {
+            clay::ElementId::new_id("Input".into()).attach(builder);
+            clay::Layout::new()
+                .sizing(clay::Sizing::fixed(200., 100.))
+                .padding(10, 0)
+                .attach(builder);
+        });
+    });
+    let texts = commands
+        .into_iter()
+        .filter_map(|command| match command {
+            clay::OwnedRenderCommand::Text { text, .. } => Some(text),
+            _ => None,
+        })
+        .collect();
+    (response, texts)
+}
+
+fn key(key: clay::Key, modifiers: clay::Modifiers) -> clay::KeyEvent {
+    clay::KeyEvent::Key { key, modifiers }
+}
+
+#[test]
+fn test_text_input() {
+    let _clay = CLAY.lock().unwrap();
+    let mut context =
+        clay::Context::with_capacity(clay::Dimensions::new(300.0, 300.0), clay::default());
+    context
+        .arena()
+        .set_measure_text_callback(clay::testing::measure_text);
+    // Chars are 5 wide
+    let config = clay::Text::new().size(10);
+    let none = clay::Modifiers::default();
+    let shift = clay::Modifiers {
+        shift: true,
+        ..clay::default()
+    };
+    let control = clay::Modifiers {
+        control: true,
+        ..clay::default()
+    };
+    let mut text = String::from("hello");
+    let mut frame = |text: &mut String, pointer: (f32, bool), key_events: &[clay::KeyEvent]| {
+        let input = clay::TextInput::new(text).text_config(config);
+        text_input_frame(&mut context, input, pointer, key_events)
+    };
+
+    let (response, texts) = frame(&mut text, (250., false), &[]);
+    assert!(!response.focused);
+    assert_eq!(texts, ["hello"]);
+    // Pressing between "he" and "llo" focuses the input and places the cursor
+    assert!(frame(&mut text, (21., true), &[]).0.focused);
+    let (response, _) = frame(&mut text, (21., false), &[clay::KeyEvent::Text('X')]);
+    assert!(response.changed);
+    assert_eq!(text, "heXllo");
+
+    // The selection is split into its own Text element
+    let (_, texts) = frame(&mut text, (21., false), &[key(clay::Key::End, shift)]);
+    assert_eq!(texts, ["heX", "llo"]);
+    frame(&mut text, (21., false), &[key(clay::Key::Backspace, none)]);
+    assert_eq!(text, "heX");
+
+    // The arena's clipboard is shared by its inputs by default
+    let cut = [
+        key(clay::Key::Character('a'), control),
+        key(clay::Key::Character('x'), control),
+    ];
+    frame(&mut text, (21., false), &cut);
+    assert_eq!(text, "");
+    let paste = key(clay::Key::Character('v'), control);
+    frame(&mut text, (21., false), &[paste, paste]);
+    assert_eq!(text, "heXheX");
+
+    // Word navigation and deletion
+    text = String::from("foo bar");
+    let events = [
+        key(clay::Key::Home, none),
+        key(clay::Key::Right, control),
+        clay::KeyEvent::Text('!'),
+        key(clay::Key::Right, control),
+        key(clay::Key::Backspace, control),
+    ];
+    frame(&mut text, (21., false), &events);
+    assert_eq!(text, "foo! ");
+
+    let (response, _) = frame(&mut text, (21., false), &[key(clay::Key::Enter, none)]);
+    assert!(response.submitted && !response.changed);
+    // Pressing outside the input clears the focus
+    frame(&mut text, (250., true), &[]);
+    assert!(!frame(&mut text, (250., false), &[]).0.focused);
+}
+
+#[test]
+fn test_multiline_text_input() {
+    let _clay = CLAY.lock().unwrap();
+    let mut context =
+        clay::Context::with_capacity(clay::Dimensions::new(300.0, 300.0), clay::default());
+    context
+        .arena()
+        .set_measure_text_callback(clay::testing::measure_text);
+    // Chars are 5 wide, so 36 fit in the input
+    let config = clay::Text::new().size(10);
+    let mut text = format!("{}\nline", ["word"; 10].join(" "));
+    let frame = |context: &mut clay::Context, text: &mut String, key_events: &[clay::KeyEvent]| {
+        let input = clay::TextInput::new(text)
+            .text_config(config)
+            .multiline(true);
+        text_input_frame(context, input, (250., false), key_events)
+    };
+
+    // Words wrap at the width from the last layout
+    frame(&mut context, &mut text, &[]);
+    let (_, texts) = frame(&mut context, &mut text, &[]);
+    assert_eq!(
+        texts,
+        [
+            "word word word word word word word",
+            "word word word",
+            "line"
+        ]
+    );
+
+    context
+        .arena()
+        .set_focus(Some(clay::ElementId::new_id("Input".into()).id()));
+    let none = clay::Modifiers::default();
+    let events = [
+        key(clay::Key::Down, none),
+        key(clay::Key::End, none),
+        key(clay::Key::Down, none),
+        clay::KeyEvent::Text('s'),
+        key(clay::Key::Enter, none),
+    ];
+    let (response, texts) = frame(&mut context, &mut text, &events);
+    assert!(response.changed && !response.submitted);
+    assert_eq!(text, format!("{}\nlines\n", ["word"; 10].join(" ")));
+    assert_eq!(texts.last().unwrap(), "lines");
+}
+
+#[test]
+fn test_owned_render_commands() {
+    let _clay = CLAY.lock().unwrap();
+    let commands = {
+        let mut context =
+            clay::Context::with_capacity(clay::Dimensions::new(300.0, 300.0), clay::default());
+        context
+            .arena()
+            .set_measure_text_callback(|text: &str, config: &clay::Text| clay::Dimensions {
+                width: (text.len() * config.font_size as usize) as f32,
+                height: config.font_size as f32,
+            });
+        context.layout(clay::Dimensions::new(300.0, 300.0), |builder| {
+            builder.build(
+                |builder| {
+                    clay::ElementId::new_id("Panel".into()).attach(builder);
+                    clay::Rectangle {
+                        color: clay::Color::rgb(255., 0., 0.),
+                        ..clay::default()
+                    }
+                    .attach(builder);
+                },
+                |builder| {
+                    clay::Text {
+                        font_size: 10,
+                        ..clay::default()
+                    }
+                    .with("Hello".into())
+                    .attach(builder);
+                },
+            )
+        })
+    };
+
+    assert_eq!(commands.len(), 2);
+    assert!(matches!(
+        &commands[0],
+        clay::OwnedRenderCommand::Rectangle { config, .. } if config.color.r == 255.
+    ));
+    match &commands[1] {
+        clay::OwnedRenderCommand::Text {
+            text, bounding_box, ..
+        } => {
+            assert_eq!(text, "Hello");
+            assert_eq!(bounding_box.width, 50.);
+        }
+        command => panic!("unexpected {command:?}"),
+    }
+}
+
+#[test]
+fn test_compute_layout_bounds() {
+    let _clay = CLAY.lock().unwrap();
+    let mut arena =
+        clay::Arena::with_capacity(clay::Dimensions::new(300.0, 300.0), clay::default());
+    let layout = arena.compute_layout(clay::Dimensions::new(300.0, 200.0), |builder| {
+        builder.build(
+            |builder| {
+                clay::ElementId::new_id("Outer".into()).attach(builder);
+                clay::Layout {
+                    sizing: clay::Sizing {
+                        width: clay::SizingAxis::grow(0., f32::MAX),
+                        height: clay::SizingAxis::grow(0., f32::MAX),
+                    },
+                    padding: clay::Padding { x: 10, y: 20 },
+                    ..clay::default()
+                }
+                .attach(builder);
+            },
+            |builder| {
+                builder.build(
+                    |builder| {
+                        clay::ElementId::new_id("Inner".into()).attach(builder);
+                        clay::Layout {
+                            sizing: clay::Sizing {
+                                width: clay::SizingAxis::fixed(50.),
+                                height: clay::SizingAxis::fixed(40.),
+                            },
+                            ..clay::default()
+                        }
+                        .attach(builder);
+                    },
+                    |builder| {
+                        builder.build(
+                            |builder| {
+                                clay::ElementId::new_id("Overlay".into()).attach(builder);
+                                clay::Floating {
+                                    parent_id: clay::ElementId::ROOT.id(),
+                                    ..clay::default()
+                                }
+                                .attach(builder);
+                                clay::Layout::new()
+                                    .sizing(clay::Sizing::expand())
+                                    .attach(builder);
+                            },
+                            clay::no_children,
+                        )
+                    },
+                )
+            },
+        )
+    });
+
+    let outer = layout
+        .bounds(clay::ElementId::new_id("Outer".into()))
+        .unwrap();
+    assert_eq!((outer.width, outer.height), (300., 200.));
+    let inner = layout
+        .bounds(clay::ElementId::new_id("Inner".into()))
+        .unwrap();
+    assert_eq!(
+        (inner.x, inner.y, inner.width, inner.height),
+        (10., 20., 50., 40.)
+    );
+    assert!(layout
+        .bounds(clay::ElementId::new_id("Missing".into()))
+        .is_none());
+    // Attached to the root instead of its parent, it covers the whole layout
+    let overlay = layout
+        .bounds(clay::ElementId::new_id("Overlay".into()))
+        .unwrap();
+    assert_eq!(
+        (overlay.x, overlay.y, overlay.width, overlay.height),
+        (0., 0., 300., 200.)
+    );
+    let root = layout.bounds(clay::ElementId::ROOT).unwrap();
+    assert_eq!((root.width, root.height), (300., 200.));
+}
+
+#[test]
+fn test_fluent_config() {
+    use clay::sizing::{fixed, grow};
+    let _clay = CLAY.lock().unwrap();
+    let mut arena =
+        clay::Arena::with_capacity(clay::Dimensions::new(300.0, 300.0), clay::default());
+    let layout = arena.compute_layout(clay::Dimensions::new(300.0, 300.0), |builder| {
+        builder.build(
+            |builder| {
+                clay::Layout::new()
+                    .direction(clay::LayoutDirection::TopToBottom)
+                    .padding(16, 16)
+                    .gap(16)
+                    .width(grow())
+                    .height(fixed(200.))
+                    .attach(builder);
+            },
+            |builder| {
+                builder.build(
+                    |builder| {
+                        clay::ElementId::new_id("Header".into()).attach(builder);
+                        clay::Layout::new()
+                            .sizing(clay::Sizing::fixed(50., 40.))
+                            .attach(builder);
+                    },
+                    clay::no_children,
+                );
+                builder.build(
+                    |builder| {
+                        clay::ElementId::new_id("Body".into()).attach(builder);
+                        clay::Layout::new()
+                            .sizing(clay::Sizing::expand())
+                            .attach(builder);
+                    },
+                    clay::no_children,
+                );
+            },
+        )
+    });
+
+    let header = layout
+        .bounds(clay::ElementId::new_id("Header".into()))
+        .unwrap();
+    assert_eq!(
+        (header.x, header.y, header.width, header.height),
+        (16., 16., 50., 40.)
+    );
+    let body = layout
+        .bounds(clay::ElementId::new_id("Body".into()))
+        .unwrap();
+    assert_eq!(
+        (body.x, body.y, body.width, body.height),
+        (16., 72., 268., 112.)
+    );
+
+    let text = clay::Text::new()
+        .font(1)
+        .size(16)
+        .color(clay::Color::rgb(255., 255., 255.));
+    assert_eq!(
+        (text.font_id, text.font_size, text.text_color.r),
+        (1, 16, 255.)
+    );
+}
+
+#[test]
+fn test_const_element_id() {
+    let strings = [
+        "",
+        "a",
+        "HeaderBar",
+        "Item",
+        "OuterContainer",
+        "hello world",
+        "ünïcödé",
+        "日本語",
+        "\u{7f}\u{80}\u{ff}",
+    ];
+    for string_id in strings {
+        for offset in [0, 1, 2, 17, 1000, u32::MAX] {
+            let expected = clay::ElementId::new_idi(string_id.into(), offset);
+            let actual = clay::ElementId::new_const_idi(string_id, offset);
+            assert_eq!(
+                format!("{expected:?}"),
+                format!("{actual:?}"),
+                "{string_id:?} {offset}"
+            );
+        }
+    }
+    let long = "x".repeat(10_000);
+    assert_eq!(
+        clay::ElementId::new_id(long.as_str().into()).id(),
+        clay::ElementId::new_const(&long).id()
+    );
+
+    const HEADER: clay::ElementId = clay::clay_id!("HeaderBar");
+    assert_eq!(
+        HEADER.id(),
+        clay::ElementId::new_id("HeaderBar".into()).id()
+    );
+    assert_eq!(
+        clay::clay_id!("Item", 3).id(),
+        clay::ElementId::new_idi("Item".into(), 3).id()
+    );
+}
+
+#[test]
+fn test_owned_text() {
+    let _clay = CLAY.lock().unwrap();
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.set_measure_text_callback(clay::testing::measure_text);
+    let texts = |commands: Vec<clay::OwnedRenderCommand>| {
+        commands
+            .into_iter()
+            .filter_map(|command| match command {
+                clay::OwnedRenderCommand::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+    for count in [1, 2] {
+        let commands = arena.layout(dimensions, |builder| {
+            let config = clay::Text::new().size(10);
+            clay::clay!(builder, layout { layout_direction: clay::LayoutDirection::TopToBottom } => {
+                config.with_owned(format!("{count} owned")).attach(builder);
+                config.with(clay::text!(builder, "{} formatted", count)).attach(builder);
+                text!("{count} in clay!", size = 10);
+            })
+        });
+        assert_eq!(
+            texts(commands),
+            [
+                format!("{count} owned"),
+                format!("{count} formatted"),
+                format!("{count} in clay!")
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_stored_text_measurements() {
+    let _clay = CLAY.lock().unwrap();
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.set_measure_text_callback(|text: &str, _: &clay::Text| clay::Dimensions {
+        width: text.parse().unwrap_or(0.),
+        height: 10.,
+    });
+    // Same length strings are stored and dropped every frame, a large one is dropped
+    // at frame 4 so the retired strings are freed along with clay's measure cache
+    for frame in 0..16 {
+        let commands = arena.layout(dimensions, |builder| {
+            let config = clay::Text::new().size(10);
+            config
+                .with(builder.store_text(&(100 + frame).to_string()))
+                .attach(builder);
+            if frame < 4 {
+                config
+                    .with(builder.store_text(&"x".repeat(100_000)))
+                    .attach(builder);
+            }
+        });
+        let widths: Vec<_> = commands
+            .iter()
+            .filter_map(|command| match command {
+                clay::OwnedRenderCommand::Text {
+                    text, bounding_box, ..
+                } if !text.starts_with('x') => Some(bounding_box.width),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(widths, [100. + frame as f32]);
+    }
+}
+
+#[test]
+fn test_image_registry() {
+    struct Registry(clay::Images<clay::Dimensions>);
+
+    impl clay::ImageRegistry for Registry {
+        type Image = clay::Dimensions;
+
+        fn images(&self) -> &clay::Images<clay::Dimensions> {
+            &self.0
+        }
+
+        fn dimensions(image: &clay::Dimensions) -> Option<clay::Dimensions> {
+            Some(*image)
+        }
+    }
+
+    let _clay = CLAY.lock().unwrap();
+    let registry = Registry(clay::Images::new());
+    registry.add_image(clay::Dimensions::new(10., 10.));
+    let handle = registry.add_image(clay::Dimensions::new(200., 100.));
+    assert_eq!(handle.index(), Some(1));
+    assert_eq!(clay::ImageHandle::default().index(), None);
+
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    let commands = arena.layout(dimensions, |builder| {
+        builder.build(
+            |builder| {
+                clay::Layout::new()
+                    .width(clay::SizingAxis::fixed(100.))
+                    .attach(builder);
+                registry.image(handle).attach(builder);
+            },
+            clay::no_children,
+        )
+    });
+    match commands.as_slice() {
+        [clay::OwnedRenderCommand::Image {
+            bounding_box,
+            config,
+            ..
+        }] => {
+            assert_eq!(config.handle, handle);
+            // Clay keeps the source aspect ratio
+            assert_eq!((bounding_box.width, bounding_box.height), (100., 50.));
+        }
+        commands => panic!("expected one image, got {commands:?}"),
+    }
+}
+
+#[test]
+fn test_custom_element() {
+    struct Chart {
+        values: Vec<f32>,
+    }
+
+    struct CustomRenderer {
+        charts: RefCell<Vec<(clay::BoundingBox, Vec<f32>)>>,
+    }
+
+    impl clay::Renderer for CustomRenderer {
+        fn prepare_frame(&self) -> clay::Dimensions {
+            clay::Dimensions::new(300.0, 300.0)
+        }
+
+        fn render(&self, render_commands: &mut clay::RenderCommandIter<'_>) {
+            for command in render_commands {
+                if let clay::RenderCommandElement::Custom(_) = command.element() {
+                    assert!(command.custom::<u32>().is_none());
+                    let chart = command.custom::<Chart>().unwrap();
+                    self.charts
+                        .borrow_mut()
+                        .push((command.bounding_box, chart.values.clone()));
+                }
+            }
+        }
+    }
+
+    let _clay = CLAY.lock().unwrap();
+    let renderer = CustomRenderer {
+        charts: RefCell::new(Vec::new()),
+    };
+    let mut arena =
+        clay::Arena::with_capacity(clay::Dimensions::new(300.0, 300.0), clay::default());
+    for frame in 0..2 {
+        arena.render(&renderer, |builder| {
+            builder.build(
+                |builder| {
+                    clay::Layout::new()
+                        .sizing(clay::Sizing::fixed(100., 50.))
+                        .attach(builder);
+                    clay::Custom::new(Chart {
+                        values: vec![frame as f32, 2.],
+                    })
+                    .attach(builder);
+                },
+                clay::no_children,
+            )
+        });
+    }
+    let charts = renderer.charts.borrow();
+    assert_eq!(charts.len(), 2);
+    assert_eq!(
+        (charts[1].0.width, charts[1].0.height, &charts[1].1),
+        (100., 50., &vec![1., 2.])
+    );
+}
+
+#[test]
+fn test_owned_custom_element() {
+    let _clay = CLAY.lock().unwrap();
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    let mut layout = |values: Vec<f32>| {
+        arena.layout(dimensions, |builder| {
+            builder.build(
+                |builder| {
+                    clay::Layout::new()
+                        .sizing(clay::Sizing::fixed(100., 50.))
+                        .attach(builder);
+                    clay::Custom::new(values).attach(builder);
+                },
+                clay::no_children,
+            )
+        })
+    };
+    let first = layout(vec![1., 2.]);
+    let second = layout(vec![3.]);
+    // The payload outlives the frame that stored it and can be drawn on another thread
+    let first = std::thread::spawn(move || first[0].custom::<Vec<f32>>().cloned())
+        .join()
+        .unwrap();
+    assert_eq!(first, Some(vec![1., 2.]));
+    assert!(second[0].custom::<u32>().is_none());
+    assert_eq!(second[0].custom::<Vec<f32>>(), Some(&vec![3.]));
+}
+
+#[test]
+fn test_snapshot() {
+    let _clay = CLAY.lock().unwrap();
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let renderer = clay::testing::SnapshotRenderer::new(dimensions);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.set_measure_text_callback(clay::testing::measure_text);
+    arena.render(&renderer, |builder| {
+        builder.build(
+            |builder| {
+                clay::ElementId::new_id("Sidebar".into()).attach(builder);
+                clay::Layout {
+                    layout_direction: clay::LayoutDirection::TopToBottom,
+                    padding: clay::Padding { x: 8, y: 8 },
+                    child_gap: 4,
+                    ..clay::default()
+                }
+                .attach(builder);
+                clay::Scroll {
+                    horizontal: false,
+                    vertical: true,
+                }
+                .attach(builder);
+                clay::Rectangle {
+                    color: clay::Color::rgb(90., 90., 90.),
+                    corner_radius: clay::CornerRadius::new(4.),
+                }
+                .attach(builder);
+                clay::Border::all_radius(1, clay::Color::rgb(0., 0., 0.), 4.).attach(builder);
+            },
+            |builder| {
+                for i in 0..2 {
+                    builder.build(
+                        |builder| {
+                            clay::ElementId::new_idi("Item".into(), i).attach(builder);
+                            clay::Rectangle {
+                                color: clay::Color::rgb(120., 120., 120.),
+                                ..clay::default()
+                            }
+                            .attach(builder);
+                        },
+                        |builder| {
+                            clay::Text {
+                                font_size: 15,
+                                text_color: clay::Color::rgb(255., 255., 255.),
+                                ..clay::default()
+                            }
+                            .with("Item".into())
+                            .attach(builder);
+                        },
+                    );
+                }
+            },
+        )
+    });
+    renderer.assert_snapshot("sidebar");
+}
+
+#[test]
+fn test_debug_mode() {
+    let _clay = CLAY.lock().unwrap();
+    let dimensions = clay::Dimensions::new(800.0, 600.0);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.set_measure_text_callback(clay::testing::measure_text);
+    let ui = |builder: &clay::Builder| {
+        builder.build(
+            |builder| clay::ElementId::new_id("Content".into()).attach(builder),
+            clay::no_children,
+        )
+    };
+    let has_inspector = |commands: &[clay::OwnedRenderCommand]| {
+        commands.iter().any(|command| {
+            matches!(command, clay::OwnedRenderCommand::Text { text, .. } if text == "Content")
+        })
+    };
+
+    assert!(!has_inspector(&arena.layout(dimensions, ui)));
+    arena.set_debug_mode(true);
+    assert!(has_inspector(&arena.layout(dimensions, ui)));
+    arena.set_debug_mode(false);
+    assert!(!has_inspector(&arena.layout(dimensions, ui)));
+}
+
+#[test]
+fn test_clay_macro() {
+    let _clay = CLAY.lock().unwrap();
+    let dimensions = clay::Dimensions::new(300.0, 300.0);
+    let renderer = clay::testing::SnapshotRenderer::new(dimensions);
+    let mut arena = clay::Arena::with_capacity(dimensions, clay::default());
+    arena.set_measure_text_callback(clay::testing::measure_text);
+    arena.render(&renderer, |builder| {
+        clay::clay!(
+            builder,
+            id("Sidebar"),
+            layout {
+                layout_direction: clay::LayoutDirection::TopToBottom,
+                padding: (8, 8),
+                child_gap: 4,
+            },
+            scroll { vertical: true },
+            rectangle { color: rgb(90, 90, 90), corner_radius: 4 },
+            clay::Border::all_radius(1, clay::Color::rgb(0., 0., 0.), 4.),
+            => {
+                for i in 0..2 {
+                    clay::clay!(builder, idi("Item", i), rectangle { color: rgb(120, 120, 120) } => {
+                        text!("Item", size = 15, color = rgb(255, 255, 255));
+                    });
+                }
+            }
+        )
+    });
+    // Same tree as test_snapshot
+    renderer.assert_snapshot("sidebar");
+}